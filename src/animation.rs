@@ -0,0 +1,239 @@
+//! Live ANSI terminal view of a running machine.
+//!
+//! The tape is drawn as a fixed-width strip that scrolls to keep the head in view. Keys are
+//! read unbuffered by switching the terminal with `stty`, and the previous settings are
+//! restored on exit.
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use turing::Machine;
+use turing::constants::{BLANK, LEFT_WALL, RIGHT_WALL};
+use turing::simulator::{Outcome, Simulator, Step};
+use turing::tape::Tape;
+
+const FRAMES_PER_SECOND: f64 = 30.0;
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 1_000_000.0;
+/// Cells kept between the head and the edge of the strip before it scrolls.
+const MARGIN: i64 = 5;
+
+const RESET: &str = "\x1b[0m";
+const HEAD: &str = "\x1b[7;1m";
+const WALL: &str = "\x1b[1;33m";
+const BLANK_CELL: &str = "\x1b[2m";
+
+pub struct AnimationOptions {
+    pub speed: f64,
+    pub width: usize,
+    pub max_steps: u64,
+}
+
+/// Unbuffered, unechoed terminal input for as long as it is alive.
+struct RawMode {
+    saved: Option<String>,
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl RawMode {
+    fn enable() -> Self {
+        let saved = stty(&["-g"]);
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "-isig", "min", "1"]);
+        }
+        RawMode { saved }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            stty(&[saved]);
+        }
+    }
+}
+
+fn spawn_key_reader() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buffer = [0; 16];
+        while let Ok(n @ 1..) = stdin.read(&mut buffer) {
+            if buffer[..n].iter().any(|&b| sender.send(b).is_err()) {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+fn paint(symbol: char, is_head: bool) -> String {
+    let wall = symbol == LEFT_WALL || symbol == RIGHT_WALL;
+    let style = match (is_head, wall) {
+        (true, true) => format!("{HEAD}{WALL}"),
+        (true, false) => HEAD.to_string(),
+        (false, true) => WALL.to_string(),
+        (false, false) if symbol == BLANK => BLANK_CELL.to_string(),
+        (false, false) => return format!(" {symbol} "),
+    };
+    format!("{style} {symbol} {RESET}")
+}
+
+struct View {
+    left: i64,
+    width: i64,
+}
+
+impl View {
+    fn follow(&mut self, head: i64) {
+        let margin = MARGIN.min((self.width - 1) / 2);
+        if head < self.left + margin {
+            self.left = head - margin;
+        } else if head > self.left + self.width - 1 - margin {
+            self.left = head - self.width + 1 + margin;
+        }
+    }
+}
+
+fn frame(
+    simulator: &Simulator,
+    view: &View,
+    speed: f64,
+    paused: bool,
+    outcome: Option<&Outcome>,
+) -> String {
+    let config = &simulator.config;
+    let strip: String = (view.left..view.left + view.width)
+        .map(|p| paint(config.tape.read(p), p == config.head))
+        .collect();
+    let status = match outcome {
+        Some(outcome) => outcome.to_string(),
+        None if paused => "paused".to_string(),
+        None => "running".to_string(),
+    };
+    format!(
+        "\x1b[H\x1b[J\
+         state {}\x1b[K\n\
+         step  {}\x1b[K\n\
+         speed {speed} steps/s  ({status})\x1b[K\n\n\
+         {strip}\n\n\
+         space pause/resume  . step  + faster  - slower  q quit\n",
+        simulator.state(),
+        config.steps
+    )
+}
+
+/// Sleeps for `delay`, in slices of at most one frame, or until a key arrives, which is
+/// returned.
+fn wait(delay: Duration, keys: Option<&Receiver<u8>>) -> Option<u8> {
+    let deadline = Instant::now() + delay;
+    let slice = Duration::from_secs_f64(1.0 / FRAMES_PER_SECOND);
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return None;
+        }
+        let nap = remaining.min(slice);
+        match keys.map(|keys| keys.recv_timeout(nap)) {
+            Some(Ok(key)) => return Some(key),
+            Some(Err(RecvTimeoutError::Timeout)) => {}
+            Some(Err(RecvTimeoutError::Disconnected)) | None => thread::sleep(nap),
+        }
+    }
+}
+
+/// Runs the machine on screen until it stops, or returns `None` if the user quits first.
+pub fn animate(
+    machine: &Machine,
+    input: &str,
+    options: &AnimationOptions,
+) -> io::Result<Option<Outcome>> {
+    let interactive = io::stdin().is_terminal();
+    let _raw = interactive.then(RawMode::enable);
+    let keys = interactive.then(spawn_key_reader);
+
+    let mut out = io::stdout().lock();
+    write!(out, "\x1b[?25l")?;
+    let mut simulator = Simulator::new(machine, input);
+    let mut view = View {
+        left: 0,
+        width: options.width.max(1) as i64,
+    };
+    view.follow(0);
+    let mut speed = options.speed.clamp(MIN_SPEED, MAX_SPEED);
+    let mut paused = false;
+    let mut woken_by = None;
+
+    let result = loop {
+        let mut single_step = false;
+        let mut quit = false;
+        let pressed = woken_by.take().into_iter();
+        for key in pressed.chain(keys.iter().flat_map(|k| k.try_iter())) {
+            match key {
+                b' ' | b'p' => paused = !paused,
+                b'.' | b's' => single_step = true,
+                b'+' | b'=' => speed = (speed * 2.0).min(MAX_SPEED),
+                b'-' | b'_' => speed = (speed / 2.0).max(MIN_SPEED),
+                b'q' | 0x03 => quit = true,
+                _ => {}
+            }
+        }
+        if quit {
+            break None;
+        }
+
+        let steps_per_frame = (speed / FRAMES_PER_SECOND).ceil().max(1.0) as u64;
+        let budget = if single_step {
+            1
+        } else if paused {
+            0
+        } else {
+            steps_per_frame
+        };
+        let mut outcome = None;
+        for _ in 0..budget {
+            if simulator.config.steps >= options.max_steps && !simulator.is_halted() {
+                outcome = Some(Outcome::StepLimit);
+                break;
+            }
+            if let Step::Done(done) = simulator.step() {
+                outcome = Some(done);
+                break;
+            }
+        }
+        view.follow(simulator.config.head);
+        write!(
+            out,
+            "{}",
+            frame(&simulator, &view, speed, paused, outcome.as_ref())
+        )?;
+        out.flush()?;
+        if outcome.is_some() {
+            break outcome;
+        }
+        let delay = if paused {
+            1.0 / FRAMES_PER_SECOND
+        } else {
+            steps_per_frame as f64 / speed
+        };
+        woken_by = wait(Duration::from_secs_f64(delay), keys.as_ref());
+    };
+    write!(out, "\x1b[?25h")?;
+    out.flush()?;
+    Ok(result)
+}
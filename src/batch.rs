@@ -0,0 +1,186 @@
+//! Converts every machine file under a directory into a mirrored output tree.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics;
+use crate::{
+    ConversionError, ConversionReport, ExportFormat, MachineType, ParseMode, run_converter,
+};
+
+pub const MACHINE_EXTENSIONS: [&str; 4] = ["in", "json", "bb", "enc"];
+
+pub struct BatchEntry {
+    pub input: PathBuf,
+    pub result: Result<ConversionReport, ConversionError>,
+}
+
+pub fn is_machine_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MACHINE_EXTENSIONS.contains(&e))
+}
+
+/// Whether `path` is named like a JSON batch output, `x.out.json`.
+fn is_output_file(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| Path::new(stem).extension())
+        .is_some_and(|e| e == "out")
+}
+
+/// Machine files under `dir`, sorted, skipping anything inside `exclude`. Directories are
+/// compared by their canonical paths, so `exclude` is skipped however it is spelled.
+pub fn collect_machine_files(dir: &Path, exclude: &Path) -> io::Result<Vec<PathBuf>> {
+    let exclude = fs::canonicalize(exclude).ok();
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if exclude.as_deref() != Some(fs::canonicalize(&path)?.as_path()) {
+                    pending.push(path);
+                }
+            } else if is_machine_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+pub fn run_batch(
+    input_dir: &Path,
+    output_dir: &Path,
+    target: Option<MachineType>,
+    format: ExportFormat,
+    mode: ParseMode,
+) -> io::Result<Vec<BatchEntry>> {
+    let extension = if format == ExportFormat::Json {
+        "out.json"
+    } else {
+        "out"
+    };
+    let mut files = collect_machine_files(input_dir, output_dir)?;
+    // Writing next to the inputs leaves `x.out.json` files that look like machines, so a
+    // second run would convert the results of the first.
+    if fs::canonicalize(input_dir).ok() == fs::canonicalize(output_dir).ok() {
+        files.retain(|f| !is_output_file(f));
+    }
+    let output_of = |input: &Path| {
+        let relative = input.strip_prefix(input_dir).unwrap_or(input);
+        output_dir.join(relative).with_extension(extension)
+    };
+    let mut inputs_by_output: HashMap<PathBuf, Vec<&Path>> = HashMap::new();
+    for input in &files {
+        inputs_by_output
+            .entry(output_of(input))
+            .or_default()
+            .push(input);
+    }
+
+    let mut entries = Vec::with_capacity(files.len());
+    for input in &files {
+        let output = output_of(input);
+        let result = match (input.to_str(), output.to_str()) {
+            // Files such as `x.in` and `x.bb` would overwrite each other's output.
+            _ if inputs_by_output[&output].len() > 1 => {
+                let others: Vec<String> = inputs_by_output[&output]
+                    .iter()
+                    .filter(|&&other| other != input)
+                    .map(|other| other.display().to_string())
+                    .collect();
+                Err(ConversionError::Io(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!(
+                        "{} would also be written by {}",
+                        output.display(),
+                        others.join(", ")
+                    ),
+                )))
+            }
+            (Some(input), Some(output)) => Path::new(output)
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(ConversionError::from)
+                .and_then(|()| run_converter(input, output, target, format, mode)),
+            _ => Err(ConversionError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path is not valid UTF-8",
+            ))),
+        };
+        entries.push(BatchEntry {
+            input: input.clone(),
+            result,
+        });
+    }
+    Ok(entries)
+}
+
+pub fn render_summary(entries: &[BatchEntry], input_dir: &Path) -> String {
+    let name = |e: &BatchEntry| {
+        e.input
+            .strip_prefix(input_dir)
+            .unwrap_or(&e.input)
+            .display()
+            .to_string()
+    };
+    let width = entries
+        .iter()
+        .map(|e| name(e).chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("file".len());
+
+    let mut out = format!(
+        "{:<width$}  {:<9}  {:>15}  {:>19}\n",
+        "file", "status", "states", "transitions"
+    );
+    for entry in entries {
+        match &entry.result {
+            Ok(report) => out.push_str(&format!(
+                "{:<width$}  {:<9}  {:>6} -> {:>6}  {:>8} -> {:>8}\n",
+                name(entry),
+                "converted",
+                report.source_states,
+                report.output_states,
+                report.source_transitions,
+                report.output_transitions
+            )),
+            Err(ConversionError::ParseError(diagnostics)) => out.push_str(&format!(
+                "{:<width$}  {:<9}  {} lines failed to parse (see below)\n",
+                name(entry),
+                "FAILED",
+                diagnostics::error_count(diagnostics)
+            )),
+            Err(e) => out.push_str(&format!(
+                "{:<width$}  {:<9}  {}\n",
+                name(entry),
+                "FAILED",
+                e
+            )),
+        }
+    }
+    for entry in entries {
+        match &entry.result {
+            Ok(report) => {
+                for warning in &report.warnings {
+                    out.push_str(&format!("\n{warning}\n"));
+                }
+            }
+            Err(e @ ConversionError::ParseError(_)) => out.push_str(&format!("\n{e}\n")),
+            Err(_) => {}
+        }
+    }
+    let failed = entries.iter().filter(|e| e.result.is_err()).count();
+    out.push_str(&format!(
+        "\n{} files: {} converted, {} failed\n",
+        entries.len(),
+        entries.len() - failed,
+        failed
+    ));
+    out
+}
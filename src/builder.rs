@@ -0,0 +1,132 @@
+//! Building machines in Rust code.
+//!
+//! [`MachineBuilder`] assembles a [`Machine`] rule by rule, and the [`tm!`](crate::tm) macro
+//! writes transitions in a compact table form:
+//!
+//! ```
+//! use turing::constants::BLANK;
+//! use turing::{Direction, MachineType, tm};
+//!
+//! let rules = tm![
+//!     q0 '0' => '1', R, q1;
+//!     q1 (BLANK) => '_', S, halt;
+//! ];
+//! assert_eq!(rules[1].to_string(), "q1 _ * * halt");
+//!
+//! let machine = tm!(Infinite: q0 '0' => '1', R, halt);
+//! assert_eq!(machine.model, MachineType::Infinite);
+//! assert_eq!(machine.rules[0].transition.direction, Direction::Right);
+//! ```
+//!
+//! A state is a name (`q0`), a literal (`0`, `"sim_0"`), `*`, or a parenthesised expression
+//! that evaluates to anything printable (`(state)`). A symbol is a character literal, a
+//! constant such as `LEFT_WALL`, or a parenthesised expression. A direction is `L`, `R`, `S`
+//! (also lowercase, or `*` for stay) or a parenthesised expression.
+
+use crate::{Direction, Machine, MachineType, Rule, Transition};
+
+pub struct MachineBuilder {
+    model: MachineType,
+    start_state: Option<String>,
+    rules: Vec<Rule>,
+    comments: Vec<String>,
+    initial_input: Option<String>,
+}
+
+impl MachineBuilder {
+    pub fn new(model: MachineType) -> Self {
+        MachineBuilder {
+            model,
+            start_state: None,
+            rules: Vec::new(),
+            comments: Vec::new(),
+            initial_input: None,
+        }
+    }
+
+    pub fn start_state(mut self, state: impl Into<String>) -> Self {
+        self.start_state = Some(state.into());
+        self
+    }
+
+    pub fn rule(
+        self,
+        from: impl Into<String>,
+        read: char,
+        write: char,
+        direction: Direction,
+        to: impl Into<String>,
+    ) -> Self {
+        self.transition(Transition::new(from, read, write, direction, to))
+    }
+
+    pub fn transition(mut self, transition: Transition) -> Self {
+        self.rules.push(Rule::new(transition));
+        self
+    }
+
+    pub fn transitions(mut self, transitions: impl IntoIterator<Item = Transition>) -> Self {
+        self.rules.extend(transitions.into_iter().map(Rule::new));
+        self
+    }
+
+    /// Adds a line to the comment block at the top of the file.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comments.push(comment.into());
+        self
+    }
+
+    pub fn initial_input(mut self, input: impl Into<String>) -> Self {
+        self.initial_input = Some(input.into());
+        self
+    }
+
+    pub fn build(self) -> Machine {
+        let mut machine = Machine::new(self.model, self.rules);
+        if let Some(start) = self.start_state {
+            machine.start_state = start;
+        }
+        machine.comments = self.comments;
+        machine.initial_input = self.initial_input;
+        machine
+    }
+}
+
+/// Transitions written as `from read => write, direction, to;`. With a leading `Sipser:` or
+/// `Infinite:` it builds a whole [`Machine`](crate::Machine) instead. See the
+/// [`builder`](crate::builder) module for the accepted forms.
+#[macro_export]
+macro_rules! tm {
+    (@state *) => { ::std::string::String::from("*") };
+    (@state ($state:expr)) => { ::std::string::ToString::to_string(&$state) };
+    (@state $state:ident) => { ::std::string::String::from(stringify!($state)) };
+    (@state $state:literal) => { ::std::string::ToString::to_string(&$state) };
+    (@symbol ($symbol:expr)) => { $symbol };
+    (@symbol $symbol:literal) => { $symbol };
+    (@symbol $symbol:ident) => { $symbol };
+    (@direction ($direction:expr)) => { $direction };
+    (@direction L) => { $crate::Direction::Left };
+    (@direction l) => { $crate::Direction::Left };
+    (@direction R) => { $crate::Direction::Right };
+    (@direction r) => { $crate::Direction::Right };
+    (@direction S) => { $crate::Direction::Stay };
+    (@direction s) => { $crate::Direction::Stay };
+    (@direction *) => { $crate::Direction::Stay };
+    (Sipser: $($rules:tt)*) => {
+        $crate::Machine::from_transitions($crate::MachineType::Sipser, $crate::tm![$($rules)*])
+    };
+    (Infinite: $($rules:tt)*) => {
+        $crate::Machine::from_transitions($crate::MachineType::Infinite, $crate::tm![$($rules)*])
+    };
+    ($($from:tt $read:tt => $write:tt, $direction:tt, $to:tt);* $(;)?) => {
+        ::std::vec![$(
+            $crate::Transition::new(
+                $crate::tm!(@state $from),
+                $crate::tm!(@symbol $read),
+                $crate::tm!(@symbol $write),
+                $crate::tm!(@direction $direction),
+                $crate::tm!(@state $to),
+            )
+        ),*]
+    };
+}
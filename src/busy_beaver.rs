@@ -0,0 +1,234 @@
+//! Busy beaver standard notation, e.g. `1RB1LB_1LA1RZ`.
+//!
+//! Each `_`-separated group describes one state, starting with `A`. Within a group,
+//! the i-th three-character entry is the rule for reading symbol `i`: the symbol to
+//! write, the direction (`L`/`R`) and the next state. `Z` is the halt state and `---`
+//! marks an undefined transition.
+//!
+//! Symbol `0` is the blank, so it maps to [`BLANK`]; the other symbols map to their digit.
+//! The halt state maps to [`HALT_PREFIX`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+use crate::constants::{BLANK, HALT_PREFIX};
+use crate::{Direction, Machine, MachineType, Transition, is_halt_state};
+
+const HALT_LETTER: char = 'Z';
+const UNDEFINED: &str = "---";
+const MAX_STATES: usize = 25;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BusyBeaverError {
+    Empty,
+    InvalidEntry(String),
+    /// Two rules of `state` read `symbol`, and the notation can only hold one.
+    DuplicateRule {
+        state: String,
+        symbol: char,
+    },
+    RaggedGroups {
+        expected: usize,
+        got: usize,
+    },
+    UnknownState(char),
+    TooManyStates(usize),
+    UnsupportedDirection(String),
+    UnsupportedSymbol(char),
+}
+
+impl Display for BusyBeaverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusyBeaverError::Empty => write!(f, "Notation is empty"),
+            BusyBeaverError::InvalidEntry(entry) => write!(f, "Invalid entry: '{entry}'"),
+            BusyBeaverError::DuplicateRule { state, symbol } => {
+                write!(
+                    f,
+                    "State '{state}' has more than one rule reading '{symbol}'"
+                )
+            }
+            BusyBeaverError::RaggedGroups { expected, got } => write!(
+                f,
+                "Every state must have {expected} entries, found a state with {got}"
+            ),
+            BusyBeaverError::UnknownState(s) => write!(f, "Transition to undefined state '{s}'"),
+            BusyBeaverError::TooManyStates(n) => {
+                write!(
+                    f,
+                    "Too many states: {n}, at most {MAX_STATES} are supported"
+                )
+            }
+            BusyBeaverError::UnsupportedDirection(state) => {
+                write!(f, "State '{state}' uses a stay move, which has no notation")
+            }
+            BusyBeaverError::UnsupportedSymbol(c) => {
+                write!(f, "Symbol '{c}' is not a digit or blank")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BusyBeaverError {}
+
+fn state_name(letter: char) -> String {
+    if letter == HALT_LETTER {
+        HALT_PREFIX.to_string()
+    } else {
+        letter.to_string()
+    }
+}
+
+fn symbol_char(digit: u32) -> Option<char> {
+    if digit == 0 {
+        Some(BLANK)
+    } else {
+        char::from_digit(digit, 10)
+    }
+}
+
+fn symbol_digit(symbol: char) -> Option<u32> {
+    if symbol == BLANK {
+        Some(0)
+    } else {
+        symbol.to_digit(10).filter(|&d| d > 0)
+    }
+}
+
+fn parse_entry(
+    state: char,
+    read: u32,
+    entry: &str,
+    state_count: usize,
+) -> Result<Option<Transition>, BusyBeaverError> {
+    if entry == UNDEFINED {
+        return Ok(None);
+    }
+    let invalid = || BusyBeaverError::InvalidEntry(entry.to_string());
+    let chars: Vec<char> = entry.chars().collect();
+    let [write, direction, next] = chars[..] else {
+        return Err(invalid());
+    };
+    let new_symbol = write
+        .to_digit(10)
+        .and_then(symbol_char)
+        .ok_or_else(invalid)?;
+    let direction = match direction {
+        'L' => Direction::Left,
+        'R' => Direction::Right,
+        _ => return Err(invalid()),
+    };
+    if next != HALT_LETTER {
+        let index = (next as usize).wrapping_sub('A' as usize);
+        if !next.is_ascii_uppercase() || index >= state_count {
+            return Err(BusyBeaverError::UnknownState(next));
+        }
+    }
+    Ok(Some(Transition {
+        current_state: state.to_string(),
+        current_symbol: symbol_char(read).ok_or_else(invalid)?,
+        new_symbol,
+        direction,
+        new_state: state_name(next),
+    }))
+}
+
+pub fn parse(notation: &str) -> Result<Machine, BusyBeaverError> {
+    let notation = notation.trim();
+    if notation.is_empty() {
+        return Err(BusyBeaverError::Empty);
+    }
+    let groups: Vec<&str> = notation.split('_').collect();
+    if groups.len() > MAX_STATES {
+        return Err(BusyBeaverError::TooManyStates(groups.len()));
+    }
+    let entries_per_state = groups[0].chars().count() / 3;
+
+    let mut transitions = Vec::new();
+    for (state, group) in ('A'..).zip(&groups) {
+        let chars: Vec<char> = group.chars().collect();
+        if !chars.len().is_multiple_of(3)
+            || chars.len() / 3 != entries_per_state
+            || chars.is_empty()
+        {
+            return Err(BusyBeaverError::RaggedGroups {
+                expected: entries_per_state,
+                got: chars.len() / 3,
+            });
+        }
+        for (read, entry) in (0..).zip(chars.chunks(3)) {
+            let entry: String = entry.iter().collect();
+            transitions.extend(parse_entry(state, read, &entry, groups.len())?);
+        }
+    }
+
+    let mut machine = Machine::from_transitions(MachineType::Infinite, transitions);
+    machine.start_state = "A".to_string();
+    Ok(machine)
+}
+
+pub fn to_notation(machine: &Machine) -> Result<String, BusyBeaverError> {
+    let mut letters: HashMap<&str, char> = HashMap::new();
+    let mut order: Vec<&str> = Vec::new();
+    let states =
+        std::iter::once(machine.start_state.as_str()).chain(machine.rules.iter().flat_map(|r| {
+            [
+                r.transition.current_state.as_str(),
+                r.transition.next_state(),
+            ]
+        }));
+    for state in states {
+        if !is_halt_state(state) && !letters.contains_key(state) {
+            if order.len() == MAX_STATES {
+                return Err(BusyBeaverError::TooManyStates(order.len() + 1));
+            }
+            letters.insert(state, (b'A' + order.len() as u8) as char);
+            order.push(state);
+        }
+    }
+
+    let mut table: BTreeMap<(&str, u32), String> = BTreeMap::new();
+    let mut symbol_count = 2;
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        let (written, next) = (t.written_symbol(), t.next_state());
+        let read = symbol_digit(t.current_symbol)
+            .ok_or(BusyBeaverError::UnsupportedSymbol(t.current_symbol))?;
+        let write = symbol_digit(written).ok_or(BusyBeaverError::UnsupportedSymbol(written))?;
+        let direction = match t.direction {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+            Direction::Stay => {
+                return Err(BusyBeaverError::UnsupportedDirection(
+                    t.current_state.clone(),
+                ));
+            }
+        };
+        let next = if is_halt_state(next) {
+            HALT_LETTER
+        } else {
+            letters[next]
+        };
+        symbol_count = symbol_count.max(read + 1).max(write + 1);
+        let entry = format!("{write}{direction}{next}");
+        if table
+            .insert((t.current_state.as_str(), read), entry)
+            .is_some()
+        {
+            return Err(BusyBeaverError::DuplicateRule {
+                state: t.current_state.clone(),
+                symbol: t.current_symbol,
+            });
+        }
+    }
+
+    Ok(order
+        .iter()
+        .map(|state| {
+            (0..symbol_count)
+                .map(|read| table.get(&(*state, read)).map_or(UNDEFINED, String::as_str))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("_"))
+}
@@ -0,0 +1,894 @@
+//! Command-line interface.
+//!
+//! ```text
+//! turing convert  [FILE] [-o OUT] [--to sipser|infinite] [--format FORMAT]
+//! turing simulate [FILE] [-o OUT] [--input SYMBOLS] [--max-steps N] [--converted] [--trace]
+//!                 [--fast [--no-macro]] [--bench] [--rle]
+//!                 [--snapshot PATH [--snapshot-every N]] [--resume PATH]
+//! turing verify   [FILE] [-o OUT] [--input SYMBOLS]... [--max-length N] [--max-steps N]
+//! turing lint     [FILE] [-o OUT]
+//! turing export   [FILE] [-o OUT] --format FORMAT [--converted]
+//! turing fmt     [FILE] [-o OUT] [--check | -w]
+//! turing stats   [FILE] [-o OUT] [--to sipser|infinite] [--format text|json]
+//! turing diff    OLD NEW [-o OUT]
+//! turing batch    DIR [-o OUTDIR] [--to sipser|infinite] [--format FORMAT]
+//! turing debug    [FILE] [--input SYMBOLS] [--max-steps N] [--converted]
+//! turing animate  [FILE] [--input SYMBOLS] [--speed N] [--width N] [--converted]
+//! turing watch    FILE... [--to sipser|infinite] [--input SYMBOLS]... [--interval MS] [-w]
+//! ```
+//!
+//! `FILE` defaults to standard input (also spelled `-`), whose format is detected from its
+//! contents. Output goes to standard output unless `-o` is given, except for `convert` on a
+//! file, which keeps writing `<name>.out` next to the input. Invoking the binary without a
+//! subcommand behaves like `convert`, and with no arguments at all converts `example.in`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::animation::{self, AnimationOptions};
+use crate::debugger::Debugger;
+use crate::interrupt::Interrupt;
+use crate::watch;
+use turing::batch;
+use turing::dense::DenseSimulator;
+use turing::diff;
+use turing::format;
+use turing::lint::Severity;
+use turing::model::Registry;
+use turing::simulator::{Outcome, Simulator, Step};
+use turing::snapshot::Snapshot;
+use turing::stats;
+use turing::stream;
+use turing::tape::{AnyTape, Tape};
+use turing::verify::Verdict;
+use turing::{
+    ConversionError, ExportFormat, Machine, MachineType, ParseMode, convert, convert_to,
+    export_machine, lint, parse_input, read_machine, run_converter, verify,
+};
+
+const USAGE: &str = "\
+Usage: turing <command> [options] [FILE]
+
+Commands:
+  convert    Convert a machine to the other tape model
+  simulate   Run a machine on an input
+  verify     Check that the conversion behaves like the source machine
+  lint       Report suspicious rules
+  export     Print a machine in another format
+  fmt        Print a text machine file in canonical order and layout
+  stats      Report how much the conversion grows the machine, per generator
+  diff       Compare two machines up to state renaming
+  batch      Convert every machine file under a directory
+  debug      Step through a run interactively
+  animate    Show a run as a live terminal animation
+  watch      Re-convert, lint and test files whenever they change
+
+Options:
+  -o, --output PATH     Write to PATH instead of the default ('-' for standard output);
+                        for batch, the output directory (default: DIR/out)
+  --to MODEL            convert/stats/batch: target model, 'sipser' or 'infinite'
+  --format FORMAT       convert/export/batch: text, json, bb, binary, mermaid, mermaid-collapsed,
+                        table or html; stats: text or json
+  --converted           simulate/export/debug/animate: use the converted machine
+  --input SYMBOLS       simulate/verify/debug/animate/watch: initial tape contents
+  --max-steps N         simulate/verify/debug/animate/watch: step limit (default 1000000)
+  --max-length N        verify: check every input up to this length (default 4)
+  --trace               simulate: print every configuration
+  --fast                simulate: use the dense-table simulator with macro-steps
+  --no-macro            simulate: with --fast, execute every step individually
+  --bench               simulate: run with --fast and report steps per second
+  --rle                 simulate: store the tape as runs of equal symbols
+  --snapshot PATH       simulate: save the run to PATH on Ctrl-C and at the step limit
+  --snapshot-every N    simulate: with --snapshot, also save every N steps
+  --resume PATH         simulate: carry on from a snapshot of the same machine
+  --check               fmt: only report whether the file is already formatted
+  -w, --write           fmt: rewrite the file in place; watch: write each conversion to
+                        FILE.out
+  --lenient             Accept multi-character symbols (only the first character counts)
+                        and upper-case directions, as older versions did
+  --speed N             animate: steps per second (default 10, at least 0.125)
+  --width N             animate: number of tape cells shown (default 25)
+  --interval MS         watch: polling interval in milliseconds (default 500)
+
+FILE defaults to standard input.";
+
+const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+const DEFAULT_MAX_LENGTH: usize = 4;
+const DEFAULT_SPEED: f64 = 10.0;
+const DEFAULT_WIDTH: usize = 25;
+const DEFAULT_INTERVAL_MS: u64 = 500;
+/// Steps run between checks for Ctrl-C while saving snapshots.
+const SNAPSHOT_SLICE: u64 = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Convert,
+    Simulate,
+    Verify,
+    Lint,
+    Export,
+    Fmt,
+    Stats,
+    Diff,
+    Batch,
+    Debug,
+    Animate,
+    Watch,
+}
+
+impl Command {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "convert" => Some(Command::Convert),
+            "simulate" => Some(Command::Simulate),
+            "verify" => Some(Command::Verify),
+            "lint" => Some(Command::Lint),
+            "export" => Some(Command::Export),
+            "fmt" => Some(Command::Fmt),
+            "stats" => Some(Command::Stats),
+            "diff" => Some(Command::Diff),
+            "batch" => Some(Command::Batch),
+            "debug" => Some(Command::Debug),
+            "animate" => Some(Command::Animate),
+            "watch" => Some(Command::Watch),
+            _ => None,
+        }
+    }
+
+    fn value_options(self) -> &'static [&'static str] {
+        match self {
+            Command::Convert | Command::Stats | Command::Batch => &["--output", "--to", "--format"],
+            Command::Simulate => &[
+                "--output",
+                "--input",
+                "--max-steps",
+                "--snapshot",
+                "--snapshot-every",
+                "--resume",
+            ],
+            Command::Debug => &["--input", "--max-steps"],
+            Command::Animate => &["--input", "--max-steps", "--speed", "--width"],
+            Command::Verify => &["--output", "--input", "--max-steps", "--max-length"],
+            Command::Lint | Command::Fmt | Command::Diff => &["--output"],
+            Command::Export => &["--output", "--format"],
+            Command::Watch => &["--to", "--input", "--max-steps", "--interval"],
+        }
+    }
+
+    fn flags(self) -> &'static [&'static str] {
+        match self {
+            Command::Simulate => &[
+                "--converted",
+                "--trace",
+                "--fast",
+                "--no-macro",
+                "--bench",
+                "--rle",
+                "--lenient",
+            ],
+            Command::Fmt => &["--check", "--write", "--lenient"],
+            Command::Watch => &["--write", "--lenient"],
+            Command::Export | Command::Debug | Command::Animate => &["--converted", "--lenient"],
+            _ => &["--lenient"],
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    file: Option<String>,
+    /// Further positional files, only accepted by `watch` and `diff`.
+    more_files: Vec<String>,
+    values: HashMap<&'static str, Vec<String>>,
+    flags: Vec<&'static str>,
+}
+
+impl Options {
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(name)
+            .and_then(|v| v.last())
+            .map(String::as_str)
+    }
+
+    fn values(&self, name: &str) -> &[String] {
+        self.values.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    fn parse_mode(&self) -> ParseMode {
+        if self.flag("--lenient") {
+            ParseMode::Lenient
+        } else {
+            ParseMode::Strict
+        }
+    }
+
+    fn parsed<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.value(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid value for {name}: {value}")),
+            None => Ok(default),
+        }
+    }
+}
+
+fn parse_options(command: Command, args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let name = match arg.as_str() {
+            "-o" => "--output",
+            "-w" => "--write",
+            "-" => {
+                options.file = None;
+                continue;
+            }
+            other => other,
+        };
+        if let Some(&option) = command.value_options().iter().find(|&&o| o == name) {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {option}"))?;
+            options
+                .values
+                .entry(option)
+                .or_default()
+                .push(value.clone());
+        } else if let Some(&flag) = command.flags().iter().find(|&&f| f == name) {
+            options.flags.push(flag);
+        } else if name.starts_with('-') {
+            return Err(format!("Unknown option: {arg}"));
+        } else if options.file.is_none() {
+            options.file = Some(arg.clone());
+        } else if matches!(command, Command::Watch | Command::Diff) {
+            options.more_files.push(arg.clone());
+        } else {
+            return Err(format!("Unexpected argument: {arg}"));
+        }
+    }
+    Ok(options)
+}
+
+fn load(options: &Options) -> Result<Machine, ConversionError> {
+    let machine = match &options.file {
+        Some(path) => read_machine(path, options.parse_mode())?,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            parse_input(&content, None, options.parse_mode())?
+        }
+    };
+    Ok(warned(machine))
+}
+
+/// Prints the reading warnings of `machine` to standard error.
+fn warned(machine: Machine) -> Machine {
+    for warning in &machine.warnings {
+        eprintln!("{warning}\n");
+    }
+    machine
+}
+
+/// Standard input copied to a temporary file, for conversions that read their input twice.
+/// The file is removed when this is dropped.
+struct SpooledStdin(PathBuf);
+
+impl SpooledStdin {
+    fn new() -> std::io::Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path =
+            std::env::temp_dir().join(format!("turing-stdin-{}-{nanos}", std::process::id()));
+        let mut file = fs::File::create_new(&path)?;
+        let spooled = SpooledStdin(path);
+        std::io::copy(&mut std::io::stdin().lock(), &mut file)?;
+        Ok(spooled)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for SpooledStdin {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn emit(output: Option<&str>, content: &str) -> Result<(), ConversionError> {
+    match output {
+        None | Some("-") => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(content.as_bytes())?;
+            stdout.flush()?;
+        }
+        Some(path) => fs::write(path, content)?,
+    }
+    Ok(())
+}
+
+fn parse_model(name: &str) -> Result<MachineType, String> {
+    let registry = Registry::standard();
+    registry.find(name).ok_or_else(|| {
+        let keys: Vec<&str> = registry.models().map(|m| m.key()).collect();
+        format!("Unknown model: {name} (expected {})", keys.join(" or "))
+    })
+}
+
+fn model_name(model: MachineType) -> String {
+    model.model().name().to_string()
+}
+
+fn run_convert(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let format = options
+        .value("--format")
+        .map(str::parse::<ExportFormat>)
+        .transpose()?
+        .unwrap_or(ExportFormat::Text);
+    let target = options.value("--to").map(parse_model).transpose()?;
+
+    let output = match (options.value("--output"), &options.file) {
+        (Some(output), _) => Some(output.to_string()),
+        (None, Some(file)) => {
+            let extension = if format == ExportFormat::Json {
+                "out.json"
+            } else {
+                "out"
+            };
+            let output = Path::new(file).with_extension(extension);
+            if output == Path::new(file) {
+                return Err(format!("Refusing to overwrite the input file, use -o: {file}").into());
+            }
+            Some(
+                output
+                    .to_str()
+                    .ok_or("Could not create a valid UTF-8 output path.")?
+                    .to_string(),
+            )
+        }
+        (None, None) => None,
+    };
+
+    match (&options.file, output.as_deref()) {
+        (Some(file), Some(path)) if path != "-" => {
+            let report = run_converter(file, path, target, format, options.parse_mode())?;
+            for warning in &report.warnings {
+                eprintln!("{warning}\n");
+            }
+            println!(
+                "✅ Successfully converted to {} model.\n Input: {}\n Output: {}",
+                model_name(report.target_model),
+                file,
+                path
+            );
+        }
+        (file, output) if format == ExportFormat::Text => {
+            // The streamed conversion reads its input twice, so standard input is copied to
+            // a temporary file first.
+            let spooled = file.is_none().then(SpooledStdin::new).transpose()?;
+            let path = spooled.as_ref().map_or_else(
+                || Path::new(file.as_deref().unwrap_or_default()),
+                SpooledStdin::path,
+            );
+            let open = || fs::File::open(path).map(BufReader::new);
+            let registry = Registry::standard();
+            let mode = options.parse_mode();
+            match stream::prepare(&registry, open()?, file.as_deref(), target, mode)? {
+                Some(conversion) => {
+                    let report =
+                        match output {
+                            None | Some("-") => conversion
+                                .write(open()?, BufWriter::new(std::io::stdout().lock()))?,
+                            Some(output) => conversion
+                                .write(open()?, BufWriter::new(fs::File::create(output)?))?,
+                        };
+                    for warning in &report.warnings {
+                        eprintln!("{warning}\n");
+                    }
+                }
+                None => {
+                    let machine = match spooled {
+                        Some(_) => warned(parse_input(&fs::read_to_string(path)?, None, mode)?),
+                        None => load(options)?,
+                    };
+                    let (_, rendered) = convert_to(&machine, target, format)?;
+                    emit(output, &rendered)?;
+                }
+            }
+        }
+        (_, output) => {
+            let (_, rendered) = convert_to(&load(options)?, target, format)?;
+            emit(output, &rendered)?;
+        }
+    }
+    Ok(0)
+}
+
+fn run_simulate(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let max_steps = options.parsed("--max-steps", DEFAULT_MAX_STEPS)?;
+    let source = load(options)?;
+    let machine = if options.flag("--converted") {
+        convert(&source)?
+    } else {
+        source
+    };
+    let resumed = options.value("--resume").map(Snapshot::load).transpose()?;
+    if resumed.is_some() && options.value("--input").is_some() {
+        return Err(
+            "--input cannot be combined with --resume, the tape comes from the snapshot".into(),
+        );
+    }
+    // A resumed run starts from a blank tape that the snapshot then fills in.
+    let input = match resumed {
+        Some(_) => String::new(),
+        None => options
+            .value("--input")
+            .or(machine.initial_input.as_deref())
+            .unwrap_or("")
+            .to_string(),
+    };
+    if options.flag("--trace") && options.value("--snapshot").is_some() {
+        return Err("--snapshot cannot be combined with --trace".into());
+    }
+
+    if options.flag("--fast") || options.flag("--bench") {
+        if options.flag("--trace") || options.flag("--rle") {
+            return Err(
+                "--trace and --rle need the step-by-step simulator, drop --fast and --bench".into(),
+            );
+        }
+        let mut simulator = match &resumed {
+            Some(snapshot) => DenseSimulator::resume(&machine, snapshot)?,
+            None => DenseSimulator::new(&machine, &input),
+        };
+        simulator.macro_steps = !options.flag("--no-macro");
+        let first_step = simulator.steps;
+        let start = Instant::now();
+        let outcome = run_with_snapshots(&mut simulator, max_steps, options)?;
+        let elapsed = start.elapsed().as_secs_f64();
+        let mut out = format!(
+            "Result: {}\nSteps: {}\nHead: {}\nTape: {}\n",
+            describe(outcome, options),
+            simulator.steps,
+            simulator.head(),
+            simulator.contents()
+        );
+        if options.flag("--bench") {
+            out.push_str(&format!(
+                "Time: {elapsed:.3} s\nSpeed: {:.0} steps/s\nLookups: {}\n",
+                (simulator.steps - first_step) as f64 / elapsed.max(f64::EPSILON),
+                simulator.lookups
+            ));
+        }
+        emit(options.value("--output"), &out)?;
+        return Ok(0);
+    }
+
+    let tape = if options.flag("--rle") {
+        AnyTape::run_length(machine.model, &input)
+    } else {
+        AnyTape::for_model(machine.model, &input)
+    };
+    let mut simulator = Simulator::with_tape(&machine, tape);
+    if let Some(snapshot) = &resumed {
+        simulator.restore(snapshot)?;
+    }
+    let mut out = String::new();
+    let outcome = if options.flag("--trace") {
+        loop {
+            let config = &simulator.config;
+            let (left, right) = config.tape.bounds();
+            out.push_str(&format!(
+                "{:>8}  {:<20} {}\n",
+                config.steps,
+                simulator.state(),
+                config.render(left.min(config.head), right.max(config.head))
+            ));
+            if config.steps >= max_steps {
+                break Some(Outcome::StepLimit);
+            }
+            if let Step::Done(outcome) = simulator.step() {
+                break Some(outcome);
+            }
+        }
+    } else {
+        run_with_snapshots(&mut simulator, max_steps, options)?
+    };
+
+    let config = &simulator.config;
+    out.push_str(&format!(
+        "Result: {}\nSteps: {}\nHead: {}\nTape: {}\n",
+        describe(outcome, options),
+        config.steps,
+        config.head,
+        config.tape.contents()
+    ));
+    emit(options.value("--output"), &out)?;
+    Ok(0)
+}
+
+/// A simulator whose runs can be split into slices and saved between them.
+trait Resumable {
+    fn run_until(&mut self, max_steps: u64) -> Outcome;
+    fn steps(&self) -> u64;
+    fn snapshot(&self) -> Snapshot;
+}
+
+impl<T: Tape> Resumable for Simulator<'_, T> {
+    fn run_until(&mut self, max_steps: u64) -> Outcome {
+        self.run(max_steps)
+    }
+
+    fn steps(&self) -> u64 {
+        self.config.steps
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Simulator::snapshot(self)
+    }
+}
+
+impl Resumable for DenseSimulator {
+    fn run_until(&mut self, max_steps: u64) -> Outcome {
+        self.run(max_steps)
+    }
+
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        DenseSimulator::snapshot(self)
+    }
+}
+
+/// Runs to `max_steps`, saving a snapshot to `--snapshot` every `--snapshot-every` steps,
+/// at the step limit and on Ctrl-C. Returns `None` when interrupted.
+fn run_with_snapshots(
+    simulator: &mut impl Resumable,
+    max_steps: u64,
+    options: &Options,
+) -> Result<Option<Outcome>, Box<dyn std::error::Error>> {
+    let Some(path) = options.value("--snapshot") else {
+        return Ok(Some(simulator.run_until(max_steps)));
+    };
+    let every = options.parsed("--snapshot-every", 0u64)?;
+    let interrupt = Interrupt::catch();
+
+    let mut next_save = simulator.steps().saturating_add(every);
+    loop {
+        let mut until = max_steps.min(simulator.steps().saturating_add(SNAPSHOT_SLICE));
+        if every > 0 {
+            until = until.min(next_save);
+        }
+        let outcome = simulator.run_until(until);
+        if outcome != Outcome::StepLimit {
+            return Ok(Some(outcome));
+        }
+        let interrupted = interrupt.is_set();
+        let steps = simulator.steps();
+        if interrupted || steps >= max_steps || (every > 0 && steps >= next_save) {
+            simulator.snapshot().save(path)?;
+            next_save = steps.saturating_add(every);
+        }
+        if interrupted {
+            return Ok(None);
+        }
+        if steps >= max_steps {
+            return Ok(Some(outcome));
+        }
+    }
+}
+
+/// The `Result:` line of `simulate`, which also says where an interrupted run was saved.
+fn describe(outcome: Option<Outcome>, options: &Options) -> String {
+    match outcome {
+        Some(outcome) => outcome.to_string(),
+        None => format!(
+            "interrupted, saved to {}",
+            options.value("--snapshot").unwrap_or_default()
+        ),
+    }
+}
+
+fn run_verify(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let max_steps = options.parsed("--max-steps", DEFAULT_MAX_STEPS)?;
+    let max_length = options.parsed("--max-length", DEFAULT_MAX_LENGTH)?;
+    let machine = load(options)?;
+
+    let mut inputs: Vec<String> = options.values("--input").to_vec();
+    if inputs.is_empty() {
+        let alphabet: Vec<char> = machine
+            .alphabet
+            .iter()
+            .copied()
+            .filter(|c| !matches!(*c, '_' | '#' | '$'))
+            .collect();
+        inputs.extend(machine.initial_input.clone());
+        inputs.extend(verify::enumerate_inputs(&alphabet, max_length));
+    }
+
+    let checks = verify::verify(&machine, &inputs, max_steps)?;
+    let count = |verdict: Verdict| checks.iter().filter(|c| c.verdict == verdict).count();
+    let mut out: String = checks.iter().map(|c| format!("{c}\n")).collect();
+    out.push_str(&format!(
+        "\n{} inputs: {} ok, {} mismatched, {} inconclusive\n",
+        checks.len(),
+        count(Verdict::Match),
+        count(Verdict::Mismatch),
+        count(Verdict::Inconclusive)
+    ));
+    emit(options.value("--output"), &out)?;
+    Ok(if count(Verdict::Mismatch) > 0 { 1 } else { 0 })
+}
+
+fn run_lint(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let machine = load(options)?;
+    let lints = lint::check(&machine);
+    let file = options.file.as_deref().unwrap_or("<stdin>");
+    let out: String = lints.iter().map(|l| format!("{file}:{l}\n")).collect();
+    emit(options.value("--output"), &out)?;
+    Ok(if lints.iter().any(|l| l.severity == Severity::Error) {
+        1
+    } else {
+        0
+    })
+}
+
+fn run_export(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let format = options
+        .value("--format")
+        .ok_or("Missing --format")?
+        .parse::<ExportFormat>()?;
+    let machine = load(options)?;
+    let machine = if options.flag("--converted") {
+        convert(&machine)?
+    } else {
+        machine
+    };
+    emit(
+        options.value("--output"),
+        &export_machine(&machine, format)?,
+    )?;
+    Ok(0)
+}
+
+fn run_fmt(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let original = match &options.file {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    let extension = options
+        .file
+        .as_deref()
+        .and_then(|file| Path::new(file).extension())
+        .and_then(|e| e.to_str());
+    let machine = match &options.file {
+        Some(_) => load(options)?,
+        None => parse_input(&original, None, options.parse_mode())?,
+    };
+    let formatted = format::format(&machine);
+    let file = options.file.as_deref().unwrap_or("<stdin>");
+    let is_text = !matches!(extension, Some(e) if batch::MACHINE_EXTENSIONS.contains(&e) && e != "in")
+        && original.trim_start().starts_with(';');
+    let lost = if is_text {
+        format::lost_comments(&original, &formatted)
+    } else {
+        Vec::new()
+    };
+    if !lost.is_empty() {
+        let mut message = format!("Formatting {file} would drop these comments:");
+        for comment in &lost {
+            message.push_str(&format!("\n  ;{comment}"));
+        }
+        return Err(message.into());
+    }
+
+    if options.flag("--check") {
+        if original == formatted {
+            return Ok(0);
+        }
+        println!("{file} is not formatted");
+        return Ok(1);
+    }
+    let output = match (options.value("--output"), options.flag("--write")) {
+        (Some(_), true) => return Err("--write cannot be combined with -o".into()),
+        (Some(output), false) => Some(output),
+        (None, true) => {
+            let Some(file) = options.file.as_deref() else {
+                return Err("--write needs a file".into());
+            };
+            if !is_text {
+                return Err(
+                    format!("Refusing to rewrite a non-text file as text, use -o: {file}").into(),
+                );
+            }
+            Some(file)
+        }
+        (None, false) => None,
+    };
+    emit(output, &formatted)?;
+    Ok(0)
+}
+
+fn run_stats(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let target = options.value("--to").map(parse_model).transpose()?;
+    let machine = load(options)?;
+    let (converted, _) = convert_to(&machine, target, ExportFormat::Text)?;
+    let report = stats::collect(&machine, &converted);
+    let out = match options.value("--format").unwrap_or("text") {
+        "text" => stats::to_text(&report),
+        "json" => format!("{}\n", stats::to_json(&report)),
+        other => {
+            return Err(format!("Unknown stats format: {other} (expected text or json)").into());
+        }
+    };
+    emit(options.value("--output"), &out)?;
+    Ok(0)
+}
+
+fn run_diff(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let (Some(old_path), [new_path]) = (&options.file, options.more_files.as_slice()) else {
+        return Err("diff needs exactly two machine files".into());
+    };
+    let old = read_machine(old_path, options.parse_mode())?;
+    let new = read_machine(new_path, options.parse_mode())?;
+    let result = diff::diff(&old, &new);
+    emit(
+        options.value("--output"),
+        &diff::render(&result, &old, &new),
+    )?;
+    Ok(if result.is_isomorphic() { 0 } else { 1 })
+}
+
+fn run_batch(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let format = options
+        .value("--format")
+        .map(str::parse::<ExportFormat>)
+        .transpose()?
+        .unwrap_or(ExportFormat::Text);
+    let target = options.value("--to").map(parse_model).transpose()?;
+    let input_dir = Path::new(options.file.as_deref().ok_or("Missing input directory")?);
+    let output_dir = match options.value("--output") {
+        Some(dir) => PathBuf::from(dir),
+        None => input_dir.join("out"),
+    };
+
+    let entries = batch::run_batch(input_dir, &output_dir, target, format, options.parse_mode())?;
+    if entries.is_empty() {
+        return Err(format!("No machine files found under {}", input_dir.display()).into());
+    }
+    print!("{}", batch::render_summary(&entries, input_dir));
+    Ok(if entries.iter().any(|e| e.result.is_err()) {
+        1
+    } else {
+        0
+    })
+}
+
+fn run_debug(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let max_steps = options.parsed("--max-steps", DEFAULT_MAX_STEPS)?;
+    if options.file.is_none() {
+        return Err("The debugger reads commands from standard input; pass a machine file".into());
+    }
+    let source = load(options)?;
+    let machine = if options.flag("--converted") {
+        convert(&source)?
+    } else {
+        source
+    };
+    let input = options
+        .value("--input")
+        .or(machine.initial_input.as_deref())
+        .unwrap_or("");
+    let mut debugger = Debugger::new(&machine, input, max_steps);
+    debugger.repl(std::io::stdin().lock(), std::io::stdout().lock())?;
+    Ok(0)
+}
+
+fn run_animate(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let speed = options.parsed("--speed", DEFAULT_SPEED)?;
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(format!("Invalid value for --speed: {speed}").into());
+    }
+    let animation = AnimationOptions {
+        speed,
+        width: options.parsed("--width", DEFAULT_WIDTH)?,
+        max_steps: options.parsed("--max-steps", DEFAULT_MAX_STEPS)?,
+    };
+    let source = load(options)?;
+    let machine = if options.flag("--converted") {
+        convert(&source)?
+    } else {
+        source
+    };
+    let input = options
+        .value("--input")
+        .or(machine.initial_input.as_deref())
+        .unwrap_or("");
+    animation::animate(&machine, input, &animation)?;
+    Ok(0)
+}
+
+fn run_watch(options: &Options) -> Result<i32, Box<dyn std::error::Error>> {
+    let paths: Vec<PathBuf> = options
+        .file
+        .iter()
+        .chain(&options.more_files)
+        .map(PathBuf::from)
+        .collect();
+    if paths.is_empty() {
+        return Err("Missing file to watch".into());
+    }
+    let options = watch::WatchOptions {
+        target: options.value("--to").map(parse_model).transpose()?,
+        inputs: options.values("--input").to_vec(),
+        max_steps: options.parsed("--max-steps", DEFAULT_MAX_STEPS)?,
+        interval: Duration::from_millis(options.parsed("--interval", DEFAULT_INTERVAL_MS)?),
+        mode: options.parse_mode(),
+        write: options.flag("--write"),
+    };
+    watch::watch(&paths, &options)
+}
+
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        let _ = writeln!(std::io::stdout(), "{USAGE}");
+        return 0;
+    }
+    let (command, rest) = match args.first().and_then(|a| Command::parse(a)) {
+        Some(command) => (command, &args[1..]),
+        None if args.is_empty() => (Command::Convert, &[] as &[String]),
+        None => (Command::Convert, args),
+    };
+    let mut options = match parse_options(command, rest) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+    if args.is_empty() {
+        options.file = Some("example.in".to_string());
+    }
+
+    let result = match command {
+        Command::Convert => run_convert(&options),
+        Command::Simulate => run_simulate(&options),
+        Command::Verify => run_verify(&options),
+        Command::Lint => run_lint(&options),
+        Command::Export => run_export(&options),
+        Command::Fmt => run_fmt(&options),
+        Command::Stats => run_stats(&options),
+        Command::Diff => run_diff(&options),
+        Command::Batch => run_batch(&options),
+        Command::Debug => run_debug(&options),
+        Command::Animate => run_animate(&options),
+        Command::Watch => run_watch(&options),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            match e.downcast_ref::<ConversionError>() {
+                Some(ConversionError::ParseError(_)) => eprintln!("{e}"),
+                _ => eprintln!("Error: {e}"),
+            }
+            1
+        }
+    }
+}
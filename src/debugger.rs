@@ -0,0 +1,287 @@
+//! Interactive step debugger on top of the simulator.
+//!
+//! Reads one command per line and prints the resulting configuration. Besides the `!`
+//! breakpoints from the machine file, breakpoints can be set on a state or on a
+//! `(state, symbol)` pair; `continue` stops before a rule fires on any of them. Every step
+//! records what it overwrote, so `back` can undo steps one at a time.
+
+use std::io::{self, BufRead, Write};
+
+use turing::Machine;
+use turing::intern::StateId;
+use turing::simulator::{Outcome, Simulator, Step};
+use turing::tape::Tape;
+
+const HELP: &str = "\
+Commands:
+  s, step [N]            Execute N steps (default 1)
+  c, continue            Run until a breakpoint, halt or the step limit
+  b, break STATE [SYM]   Break before a rule fires in STATE (reading SYM)
+  d, delete N            Remove breakpoint N ('delete' alone removes all)
+  l, list                List breakpoints
+  u, back [N]            Undo N steps (default 1)
+  t, tape [RADIUS]       Print the tape around the head (default radius 10)
+  n, next                Show the rule that fires next
+  r, reset               Restart from the initial configuration
+  h, help                Show this help
+  q, quit                Leave the debugger";
+
+const DEFAULT_RADIUS: i64 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Breakpoint {
+    state: String,
+    symbol: Option<char>,
+}
+
+impl Breakpoint {
+    fn matches(&self, state: &str, symbol: char) -> bool {
+        self.state == state && self.symbol.is_none_or(|s| s == symbol)
+    }
+}
+
+/// What a step changed, enough to put it back.
+struct Undo {
+    state: StateId,
+    head: i64,
+    symbol: char,
+}
+
+pub struct Debugger<'m> {
+    machine: &'m Machine,
+    input: String,
+    simulator: Simulator<'m>,
+    history: Vec<Undo>,
+    breakpoints: Vec<Breakpoint>,
+    max_steps: u64,
+    outcome: Option<Outcome>,
+}
+
+impl<'m> Debugger<'m> {
+    pub fn new(machine: &'m Machine, input: &str, max_steps: u64) -> Self {
+        Debugger {
+            machine,
+            input: input.to_string(),
+            simulator: Simulator::new(machine, input),
+            history: Vec::new(),
+            breakpoints: Vec::new(),
+            max_steps,
+            outcome: None,
+        }
+    }
+
+    fn step(&mut self) -> Step {
+        let config = &self.simulator.config;
+        let undo = Undo {
+            state: config.state,
+            head: config.head,
+            symbol: config.tape.read(config.head),
+        };
+        let step = self.simulator.step();
+        match &step {
+            Step::Fired(_) => self.history.push(undo),
+            Step::Done(outcome) => self.outcome = Some(outcome.clone()),
+        }
+        step
+    }
+
+    fn back(&mut self) -> bool {
+        let Some(undo) = self.history.pop() else {
+            return false;
+        };
+        let config = &mut self.simulator.config;
+        config.tape.write(undo.head, undo.symbol);
+        config.head = undo.head;
+        config.state = undo.state;
+        config.steps -= 1;
+        self.outcome = None;
+        true
+    }
+
+    /// Why execution should pause before the next rule fires, if it should.
+    fn breakpoint_hit(&self) -> Option<String> {
+        let config = &self.simulator.config;
+        let symbol = config.tape.read(config.head);
+        if let Some(index) = self
+            .breakpoints
+            .iter()
+            .position(|b| b.matches(self.simulator.state(), symbol))
+        {
+            return Some(format!("breakpoint {}", index + 1));
+        }
+        let rule = &self.machine.rules[self.simulator.next_rule()?];
+        rule.breakpoint.then(|| match rule.line {
+            Some(line) => format!("breakpoint on line {line}"),
+            None => "breakpoint in the machine".to_string(),
+        })
+    }
+
+    fn run_to_breakpoint(&mut self) -> String {
+        let mut first = true;
+        loop {
+            if !first && let Some(reason) = self.breakpoint_hit() {
+                return format!("Stopped at {reason}\n");
+            }
+            first = false;
+            if self.simulator.config.steps >= self.max_steps && !self.simulator.is_halted() {
+                return format!("Stopped: {}\n", Outcome::StepLimit);
+            }
+            if let Step::Done(outcome) = self.step() {
+                return format!("Stopped: {outcome}\n");
+            }
+        }
+    }
+
+    fn status(&self) -> String {
+        let config = &self.simulator.config;
+        let radius = DEFAULT_RADIUS;
+        format!(
+            "step {}  state {}  head {}\n  {}\n",
+            config.steps,
+            self.simulator.state(),
+            config.head,
+            config.render(config.head - radius, config.head + radius)
+        )
+    }
+
+    fn next(&self) -> String {
+        if let Some(outcome) = &self.outcome {
+            return format!("No rule fires: {outcome}\n");
+        }
+        let config = &self.simulator.config;
+        if self.simulator.is_halted() {
+            return format!(
+                "No rule fires: halted in state {}\n",
+                self.simulator.state()
+            );
+        }
+        match self.simulator.next_rule() {
+            Some(index) => {
+                let rule = &self.machine.rules[index];
+                let line = rule.line.map_or(String::new(), |l| format!("line {l}: "));
+                format!("{line}{}\n", rule.transition)
+            }
+            None => format!(
+                "No rule for state {} reading '{}'\n",
+                self.simulator.state(),
+                config.tape.read(config.head)
+            ),
+        }
+    }
+
+    fn count(argument: Option<&str>) -> Result<u64, String> {
+        argument.map_or(Ok(1), |n| {
+            n.parse().map_err(|_| format!("Not a number: {n}"))
+        })
+    }
+
+    /// Runs one command line. Returns `None` when the debugger should exit.
+    pub fn execute(&mut self, line: &str) -> Option<String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, arguments)) = words.split_first() else {
+            return Some(String::new());
+        };
+        let argument = arguments.first().copied();
+        let result = match command {
+            "s" | "step" => Self::count(argument).map(|n| {
+                let mut out = String::new();
+                for _ in 0..n {
+                    if let Step::Done(outcome) = self.step() {
+                        out = format!("Stopped: {outcome}\n");
+                        break;
+                    }
+                }
+                out + &self.status()
+            }),
+            "c" | "continue" => Ok(self.run_to_breakpoint() + &self.status()),
+            "b" | "break" => match arguments {
+                [state] | [state, _] => {
+                    let symbol = match arguments.get(1) {
+                        Some(s) if s.chars().count() == 1 => s.chars().next(),
+                        Some(s) => return Some(format!("Symbols are single characters: {s}\n")),
+                        None => None,
+                    };
+                    self.breakpoints.push(Breakpoint {
+                        state: state.to_string(),
+                        symbol,
+                    });
+                    Ok(format!("Breakpoint {} set\n", self.breakpoints.len()))
+                }
+                _ => Err("Usage: break STATE [SYMBOL]".to_string()),
+            },
+            "d" | "delete" => match argument {
+                None => {
+                    self.breakpoints.clear();
+                    Ok("All breakpoints removed\n".to_string())
+                }
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) if (1..=self.breakpoints.len()).contains(&n) => {
+                        self.breakpoints.remove(n - 1);
+                        Ok(format!("Breakpoint {n} removed\n"))
+                    }
+                    _ => Err(format!("No breakpoint {n}")),
+                },
+            },
+            "l" | "list" => Ok(if self.breakpoints.is_empty() {
+                "No breakpoints\n".to_string()
+            } else {
+                self.breakpoints
+                    .iter()
+                    .enumerate()
+                    .map(|(i, b)| match b.symbol {
+                        Some(symbol) => format!("{}: {} reading '{symbol}'\n", i + 1, b.state),
+                        None => format!("{}: {}\n", i + 1, b.state),
+                    })
+                    .collect()
+            }),
+            "u" | "back" => Self::count(argument).map(|n| {
+                let undone = (0..n).take_while(|_| self.back()).count();
+                let note = if (undone as u64) < n {
+                    "At the start of the history\n"
+                } else {
+                    ""
+                };
+                format!("{note}{}", self.status())
+            }),
+            "t" | "tape" => match argument.map(str::parse::<i64>).transpose() {
+                Ok(radius) => {
+                    let config = &self.simulator.config;
+                    let radius = radius.unwrap_or(DEFAULT_RADIUS);
+                    Ok(format!(
+                        "{}\n",
+                        config.render(config.head - radius, config.head + radius)
+                    ))
+                }
+                Err(_) => Err(format!("Not a number: {}", argument.unwrap_or_default())),
+            },
+            "n" | "next" => Ok(self.next()),
+            "r" | "reset" => {
+                self.simulator = Simulator::new(self.machine, &self.input);
+                self.history.clear();
+                self.outcome = None;
+                Ok(self.status())
+            }
+            "h" | "help" | "?" => Ok(format!("{HELP}\n")),
+            "q" | "quit" | "exit" => return None,
+            other => Err(format!("Unknown command: {other} (try 'help')")),
+        };
+        Some(result.unwrap_or_else(|e| format!("{e}\n")))
+    }
+
+    pub fn repl(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "{}{}", self.status(), self.next())?;
+        let mut lines = input.lines();
+        loop {
+            write!(output, "(tm) ")?;
+            output.flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                writeln!(output)?;
+                return Ok(());
+            };
+            match self.execute(&line) {
+                Some(text) => write!(output, "{text}")?,
+                None => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Simulator for very long runs, such as busy beaver experiments.
+//!
+//! The rules are compiled once into a dense `(state, symbol)` table, with the `*` fallbacks
+//! of [`Simulator`](crate::simulator::Simulator) resolved ahead of time, and symbols are
+//! stored on the tape as small integers. With macro-steps enabled, a rule that loops on its
+//! own state while moving over a run of the symbol it reads is applied to the whole run at
+//! once. Runs produce the same outcomes, step counts and tapes as the step-by-step
+//! simulator, and [`Snapshot`]s can be passed between the two.
+
+use std::collections::HashMap;
+
+use crate::constants::{ANY, ANY_STATE, BLANK};
+use crate::intern::{StateId, StateTable};
+use crate::simulator::Outcome;
+use crate::snapshot::{Snapshot, SnapshotError, machine_hash};
+use crate::tape::{self, AnyTape, Tape};
+use crate::{Direction, Machine};
+
+type Symbol = u16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Action {
+    write: Symbol,
+    direction: Direction,
+    next: StateId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Entry {
+    Halt,
+    Stuck,
+    Fire(Action),
+}
+
+pub struct DenseSimulator {
+    machine: u64,
+    states: StateTable,
+    symbols: Vec<char>,
+    /// Row-major by state, one entry per symbol.
+    table: Vec<Entry>,
+    /// [`Tape::left_edge`] of the tape the step-by-step simulator uses for the same model.
+    left_edge: Option<i64>,
+    tape: Vec<Symbol>,
+    /// Index in `tape` of position 0, the first input cell.
+    origin: usize,
+    /// Index in `tape` of the head.
+    head: usize,
+    state: StateId,
+    pub steps: u64,
+    /// Table lookups done so far; lower than `steps` when macro-steps fire.
+    pub lookups: u64,
+    /// Apply self-looping rules to whole runs of symbols at once.
+    pub macro_steps: bool,
+}
+
+impl DenseSimulator {
+    pub fn new(machine: &Machine, input: &str) -> Self {
+        // Taken from the rules rather than `machine.alphabet`, which a JSON file may leave
+        // incomplete.
+        let read_or_written = machine
+            .rules
+            .iter()
+            .flat_map(|r| [r.transition.current_symbol, r.transition.new_symbol])
+            .filter(|&c| c != ANY);
+        let mut symbols: Vec<char> = vec![BLANK];
+        for c in read_or_written.chain(input.chars()) {
+            if !symbols.contains(&c) {
+                symbols.push(c);
+            }
+        }
+        let index: HashMap<char, Symbol> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| (c, Symbol::try_from(i).expect("too many symbols")))
+            .collect();
+
+        let mut states = StateTable::for_machine(machine);
+        let mut first: HashMap<(StateId, char), usize> = HashMap::new();
+        for (i, rule) in machine.rules.iter().enumerate() {
+            let t = &rule.transition;
+            first
+                .entry((states.intern(&t.current_state), t.current_symbol))
+                .or_insert(i);
+        }
+        let wildcard = states.get(ANY_STATE);
+
+        let width = symbols.len();
+        let mut table = vec![Entry::Stuck; states.len() * width];
+        for id in states.ids() {
+            for (s, &symbol) in symbols.iter().enumerate() {
+                let cell = &mut table[id.index() * width + s];
+                if states.is_halt(id) {
+                    *cell = Entry::Halt;
+                    continue;
+                }
+                let fallbacks = [
+                    (Some(id), symbol),
+                    (Some(id), ANY),
+                    (wildcard, symbol),
+                    (wildcard, ANY),
+                ];
+                let Some(rule) = fallbacks
+                    .iter()
+                    .find_map(|&(state, read)| first.get(&(state?, read)))
+                else {
+                    continue;
+                };
+                let t = &machine.rules[*rule].transition;
+                *cell = Entry::Fire(Action {
+                    write: if t.new_symbol == ANY {
+                        s as Symbol
+                    } else {
+                        index[&t.new_symbol]
+                    },
+                    direction: t.direction,
+                    next: if t.new_state == ANY_STATE {
+                        id
+                    } else {
+                        states.intern(&t.new_state)
+                    },
+                });
+            }
+        }
+
+        let mut tape: Vec<Symbol> = input.chars().map(|c| index[&c]).collect();
+        if tape.is_empty() {
+            tape.push(0);
+        }
+        let start = states.intern(&machine.start_state);
+        DenseSimulator {
+            machine: machine_hash(machine),
+            states,
+            symbols,
+            table,
+            left_edge: AnyTape::for_model(machine.model, "").left_edge(),
+            tape,
+            origin: 0,
+            head: 0,
+            state: start,
+            steps: 0,
+            lookups: 0,
+            macro_steps: true,
+        }
+    }
+
+    /// Name of the current state.
+    pub fn state(&self) -> &str {
+        self.states.name(self.state)
+    }
+
+    /// Head position relative to the first input cell.
+    pub fn head(&self) -> i64 {
+        self.head as i64 - self.origin as i64
+    }
+
+    /// Tape contents with the surrounding blanks trimmed.
+    pub fn contents(&self) -> String {
+        let text: String = self
+            .tape
+            .iter()
+            .map(|&s| self.symbols[s as usize])
+            .collect();
+        text.trim_matches(BLANK).to_string()
+    }
+
+    /// Carries on from `snapshot`, which must have been taken from `machine`.
+    pub fn resume(machine: &Machine, snapshot: &Snapshot) -> Result<Self, SnapshotError> {
+        snapshot.check(machine)?;
+        let mut simulator = DenseSimulator::new(machine, &snapshot.tape);
+        simulator.state = simulator
+            .states
+            .get(&snapshot.state)
+            .ok_or_else(|| SnapshotError::UnknownState(snapshot.state.clone()))?;
+        if let Some(edge) = simulator.left_edge
+            && (snapshot.left < edge || snapshot.head < edge)
+        {
+            return Err(SnapshotError::Invalid(
+                "the tape extends left of the first cell".to_string(),
+            ));
+        }
+        simulator.origin = snapshot.left.unsigned_abs() as usize;
+        while snapshot.head < -(simulator.origin as i64) {
+            simulator.grow_left();
+        }
+        simulator.head = (snapshot.head + simulator.origin as i64) as usize;
+        while simulator.head >= simulator.tape.len() {
+            simulator.grow_right();
+        }
+        simulator.steps = snapshot.steps;
+        Ok(simulator)
+    }
+
+    /// The current configuration. Blanks around the contents are left out, apart from the
+    /// first input cell.
+    pub fn snapshot(&self) -> Snapshot {
+        let written = |s: &Symbol| *s != 0;
+        let first = self.tape.iter().position(written).unwrap_or(self.origin);
+        let last = self.tape.iter().rposition(written).unwrap_or(self.origin);
+        let (first, last) = (first.min(self.origin), last.max(self.origin));
+        Snapshot {
+            machine: self.machine,
+            state: self.state().to_string(),
+            head: self.head(),
+            steps: self.steps,
+            left: first as i64 - self.origin as i64,
+            tape: self.tape[first..=last]
+                .iter()
+                .map(|&s| self.symbols[s as usize])
+                .collect(),
+        }
+    }
+
+    fn grow_left(&mut self) {
+        let extra = self.tape.len().max(64);
+        self.tape.splice(0..0, std::iter::repeat_n(0, extra));
+        self.origin += extra;
+        self.head += extra;
+    }
+
+    fn grow_right(&mut self) {
+        let extra = self.tape.len().max(64);
+        self.tape.resize(self.tape.len() + extra, 0);
+    }
+
+    /// Runs until the machine halts, gets stuck or has taken `max_steps` steps in total.
+    pub fn run(&mut self, max_steps: u64) -> Outcome {
+        let width = self.symbols.len();
+        loop {
+            let read = self.tape[self.head];
+            let entry = self.table[self.state.index() * width + read as usize];
+            if entry == Entry::Halt {
+                return Outcome::Halted(self.state().to_string());
+            }
+            if self.steps >= max_steps {
+                return Outcome::StepLimit;
+            }
+            let Entry::Fire(action) = entry else {
+                return Outcome::Stuck {
+                    state: self.state().to_string(),
+                    symbol: self.symbols[read as usize],
+                };
+            };
+            self.lookups += 1;
+
+            let repeat = if self.macro_steps && action.next == self.state {
+                self.run_length(read, action, max_steps - self.steps)
+            } else {
+                Some(1)
+            };
+            let Some(repeat) = repeat else {
+                // Rewrites the same cell with the same symbol until the step limit.
+                self.steps = max_steps;
+                continue;
+            };
+            let count = repeat as usize;
+            match action.direction {
+                Direction::Right => {
+                    self.tape[self.head..self.head + count].fill(action.write);
+                    self.head += count;
+                    if self.head == self.tape.len() {
+                        self.grow_right();
+                    }
+                }
+                Direction::Left => {
+                    self.tape[self.head + 1 - count..=self.head].fill(action.write);
+                    // Only the last move of a run can reach the left edge.
+                    let last = self.head() + 1 - count as i64;
+                    let to = tape::step(last, Direction::Left, self.left_edge);
+                    while to + (self.origin as i64) < 0 {
+                        self.grow_left();
+                    }
+                    self.head = (to + self.origin as i64) as usize;
+                }
+                Direction::Stay => self.tape[self.head] = action.write,
+            }
+            self.state = action.next;
+            self.steps += repeat;
+        }
+    }
+
+    /// How many times in a row `action`, which reads `read` and stays in the current state,
+    /// fires from the current configuration, at most `budget`. Only cells already on the
+    /// tape are skipped. `None` means it stays on one cell forever.
+    fn run_length(&self, read: Symbol, action: Action, budget: u64) -> Option<u64> {
+        let budget = usize::try_from(budget).unwrap_or(usize::MAX);
+        let cells = match action.direction {
+            Direction::Right => &self.tape[self.head..],
+            Direction::Left => {
+                // The tape grows on demand, so without an edge the run can cover every cell.
+                let floor = tape::run_floor(self.left_edge)
+                    .map_or(0, |floor| (floor + self.origin as i64).max(0) as usize);
+                if self.head < floor {
+                    return Some(1);
+                }
+                &self.tape[floor..=self.head]
+            }
+            Direction::Stay if action.write == read => return None,
+            Direction::Stay => return Some(1),
+        };
+        let same = |&&s: &&Symbol| s == read;
+        let run = match action.direction {
+            Direction::Left => cells.iter().rev().take(budget).take_while(same).count(),
+            _ => cells.iter().take(budget).take_while(same).count(),
+        };
+        Some(run as u64)
+    }
+}
@@ -0,0 +1,96 @@
+//! Compiler-style rendering of parse errors and warnings.
+//!
+//! ```text
+//! error: Invalid direction: x
+//!  --> example.in:12:7
+//!    |
+//! 12 | 0 0 1 x 1
+//!    |       ^
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::lint::Severity;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte range of the offending text within `source`.
+    pub span: Range<usize>,
+    pub source: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, source: &str, span: Range<usize>, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            file: None,
+            line,
+            span,
+            source: source.to_string(),
+            message,
+        }
+    }
+
+    pub fn warning(line: usize, source: &str, span: Range<usize>, message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::new(line, source, span, message)
+        }
+    }
+
+    /// 1-based column of the start of the span, counted in characters.
+    pub fn column(&self) -> usize {
+        self.source
+            .get(..self.span.start)
+            .map_or(0, |prefix| prefix.chars().count())
+            + 1
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let location = match &self.file {
+            Some(file) => format!("{file}:{}:{}", self.line, self.column()),
+            None => format!("line {}:{}", self.line, self.column()),
+        };
+        let prefix = self.source.get(..self.span.start).unwrap_or_default();
+        let padding: String = prefix
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = self
+            .source
+            .get(self.span.clone())
+            .map_or(0, |s| s.chars().count())
+            .max(1);
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+        writeln!(f, "{gutter}--> {location}")?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source)?;
+        write!(f, "{gutter} | {padding}{}", "^".repeat(width))
+    }
+}
+
+pub fn error_count(diagnostics: &[Diagnostic]) -> usize {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count()
+}
+
+/// All diagnostics separated by blank lines, followed by a count when there are several.
+pub fn render(diagnostics: &[Diagnostic]) -> String {
+    let mut out: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+    let errors = error_count(diagnostics);
+    if errors > 1 {
+        out.push(format!("error: {errors} lines failed to parse"));
+    }
+    out.join("\n\n")
+}
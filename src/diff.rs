@@ -0,0 +1,245 @@
+//! Structural comparison of two machines up to a renaming of their states.
+//!
+//! The renaming is built by walking both machines in lockstep from their start states: when
+//! `a` is paired with `b`, the rules of `a` and `b` that read the same symbol pair their
+//! next states. States the walk never reaches are paired by identical rule shape, then by
+//! identical name. Symbols are never renamed. Rules are then compared per paired state and
+//! read symbol.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use crate::constants::ANY_STATE;
+use crate::{Direction, Machine, Transition, is_halt_state};
+
+/// The first rule for each `(state, symbol)`, with a `*` written symbol resolved to the
+/// symbol read and a `*` next state to the state itself. Later duplicates can never fire, so
+/// they are ignored.
+type RuleMap<'m> = BTreeMap<&'m str, BTreeMap<char, (char, Direction, &'m str)>>;
+
+fn rule_map(machine: &Machine) -> RuleMap<'_> {
+    let mut map: RuleMap = BTreeMap::new();
+    for t in machine.rules.iter().map(|r| &r.transition) {
+        map.entry(t.current_state.as_str())
+            .or_default()
+            .entry(t.current_symbol)
+            .or_insert((t.written_symbol(), t.direction, t.next_state()));
+    }
+    map
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added(Transition),
+    Removed(Transition),
+    Changed { old: Transition, new: Transition },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(t) => write!(f, "+ {t}"),
+            Change::Removed(t) => write!(f, "- {t}"),
+            Change::Changed { old, new } => write!(f, "~ {old}  =>  {new}"),
+        }
+    }
+}
+
+pub struct MachineDiff {
+    pub models_differ: bool,
+    /// Paired states, old name first, sorted by old name.
+    pub mapping: Vec<(String, String)>,
+    pub changes: Vec<Change>,
+}
+
+impl MachineDiff {
+    pub fn is_isomorphic(&self) -> bool {
+        !self.models_differ && self.changes.is_empty()
+    }
+
+    pub fn renamed(&self) -> impl Iterator<Item = &(String, String)> {
+        self.mapping.iter().filter(|(a, b)| a != b)
+    }
+}
+
+/// Shape of a state's rules with next states reduced to "itself" or "elsewhere", used to
+/// pair states that the lockstep walk does not reach.
+fn shape(rules: Option<&BTreeMap<char, (char, Direction, &str)>>, state: &str) -> String {
+    rules
+        .into_iter()
+        .flatten()
+        .map(|(read, (write, direction, next))| {
+            let next = if *next == state { "self" } else { "other" };
+            format!("{read}{write}{direction}{next};")
+        })
+        .collect()
+}
+
+struct Pairing<'a> {
+    forward: HashMap<&'a str, &'a str>,
+    backward: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Pairing<'a> {
+    fn pair(&mut self, a: &'a str, b: &'a str) -> bool {
+        // Halting is decided by the state name, so halt states only pair with halt states.
+        if self.forward.contains_key(a)
+            || self.backward.contains_key(b)
+            || is_halt_state(a) != is_halt_state(b)
+        {
+            return false;
+        }
+        self.forward.insert(a, b);
+        self.backward.insert(b, a);
+        true
+    }
+}
+
+fn transition(
+    state: &str,
+    read: char,
+    (write, direction, next): (char, Direction, &str),
+) -> Transition {
+    Transition {
+        current_state: state.to_string(),
+        current_symbol: read,
+        new_symbol: write,
+        direction,
+        new_state: next.to_string(),
+    }
+}
+
+pub fn diff(old: &Machine, new: &Machine) -> MachineDiff {
+    let old_rules = rule_map(old);
+    let new_rules = rule_map(new);
+    let old_states = old.states();
+    let new_states = new.states();
+
+    let mut pairing = Pairing {
+        forward: HashMap::new(),
+        backward: HashMap::new(),
+    };
+    // A `*` state matches every state in both machines, so it can only pair with itself.
+    if old_rules.contains_key(ANY_STATE) && new_rules.contains_key(ANY_STATE) {
+        pairing.pair(ANY_STATE, ANY_STATE);
+    }
+
+    let mut queue = VecDeque::new();
+    if pairing.pair(&old.start_state, &new.start_state) {
+        queue.push_back((old.start_state.as_str(), new.start_state.as_str()));
+    }
+    while let Some((a, b)) = queue.pop_front() {
+        let (Some(a_rules), Some(b_rules)) = (old_rules.get(a), new_rules.get(b)) else {
+            continue;
+        };
+        for (read, &(_, _, a_next)) in a_rules {
+            if let Some(&(_, _, b_next)) = b_rules.get(read)
+                && pairing.pair(a_next, b_next)
+            {
+                queue.push_back((a_next, b_next));
+            }
+        }
+    }
+
+    let mut unpaired_new: Vec<&str> = new_states
+        .iter()
+        .copied()
+        .filter(|s| !pairing.backward.contains_key(s))
+        .collect();
+    for &a in &old_states {
+        if pairing.forward.contains_key(a) {
+            continue;
+        }
+        let a_shape = shape(old_rules.get(a), a);
+        let found = unpaired_new
+            .iter()
+            .position(|&b| shape(new_rules.get(b), b) == a_shape)
+            .or_else(|| unpaired_new.iter().position(|&b| b == a));
+        if let Some(i) = found {
+            pairing.pair(a, unpaired_new.remove(i));
+        }
+    }
+
+    let rename = |state: &str| pairing.forward.get(state).copied();
+    let mut changes = Vec::new();
+    for &a in &old_states {
+        let a_rules = old_rules.get(a);
+        let b = rename(a);
+        let b_rules = b.and_then(|b| new_rules.get(b));
+        let reads: BTreeSet<char> = a_rules
+            .into_iter()
+            .chain(b_rules)
+            .flat_map(|r| r.keys().copied())
+            .collect();
+        for read in reads {
+            let old_rule = a_rules.and_then(|r| r.get(&read)).copied();
+            let new_rule = b_rules.and_then(|r| r.get(&read)).copied();
+            match (old_rule, new_rule) {
+                (Some(o), Some(n)) => {
+                    if (o.0, o.1, rename(o.2)) != (n.0, n.1, Some(n.2)) {
+                        changes.push(Change::Changed {
+                            old: transition(a, read, o),
+                            new: transition(b.unwrap_or(a), read, n),
+                        });
+                    }
+                }
+                (Some(o), None) => changes.push(Change::Removed(transition(a, read, o))),
+                (None, Some(n)) => changes.push(Change::Added(transition(b.unwrap_or(a), read, n))),
+                (None, None) => {}
+            }
+        }
+    }
+    for (&b, b_rules) in &new_rules {
+        if !pairing.backward.contains_key(b) {
+            changes.extend(
+                b_rules
+                    .iter()
+                    .map(|(&read, &rule)| Change::Added(transition(b, read, rule))),
+            );
+        }
+    }
+
+    let mut mapping: Vec<(String, String)> = pairing
+        .forward
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+    mapping.sort();
+    MachineDiff {
+        models_differ: old.model != new.model,
+        mapping,
+        changes,
+    }
+}
+
+pub fn render(diff: &MachineDiff, old: &Machine, new: &Machine) -> String {
+    let mut out = String::new();
+    if diff.models_differ {
+        out.push_str(&format!("models differ: {} vs {}\n", old.model, new.model));
+    }
+    let renamed: Vec<_> = diff.renamed().collect();
+    if !renamed.is_empty() {
+        out.push_str("state mapping:\n");
+        for (a, b) in &renamed {
+            out.push_str(&format!("  {a} -> {b}\n"));
+        }
+    }
+    for change in &diff.changes {
+        out.push_str(&format!("{change}\n"));
+    }
+    if diff.is_isomorphic() {
+        out.push_str(&format!(
+            "The machines are identical up to renaming ({} states renamed)\n",
+            renamed.len()
+        ));
+    } else {
+        let count = |f: fn(&Change) -> bool| diff.changes.iter().filter(|c| f(c)).count();
+        out.push_str(&format!(
+            "{} added, {} removed, {} changed\n",
+            count(|c| matches!(c, Change::Added(_))),
+            count(|c| matches!(c, Change::Removed(_))),
+            count(|c| matches!(c, Change::Changed { .. }))
+        ));
+    }
+    out
+}
@@ -0,0 +1,285 @@
+//! Standard binary encoding ⟨M⟩ of a machine, following the Hopcroft–Motwani–Ullman scheme.
+//!
+//! States are numbered `q1, q2, …`, symbols `X1, X2, …` and directions `D1 = l`, `D2 = r`,
+//! `D3 = *`. A transition `δ(qi, Xj) = (qk, Xl, Dm)` is encoded as
+//! `0^i 1 0^j 1 0^k 1 0^l 1 0^m`, and the whole machine as
+//!
+//! ```text
+//! ⟨M⟩ = 0^t 1 0^(h+1) (1 0^c)* 111 C1 11 C2 11 … 11 Cn
+//! ```
+//!
+//! where `t` is the model's position in the registry, `1` = Infinite and `2` = Sipser in the
+//! [standard](crate::model::Registry::standard) one, `h` the number of halt states and
+//! each `0^c` the code point of a symbol outside the fixed table below.
+//!
+//! Numbering is canonical, so equal machines encode to equal strings:
+//!
+//! - `q1` is the start state, followed by the halt states ordered by name, followed by the
+//!   remaining states in breadth-first order from the start state (exploring transitions by
+//!   symbol number), followed by unreachable states ordered by name length, then name.
+//! - `X1 = 0`, `X2 = 1`, `X3 = _`, `X4 = *`, `X5 = #`, `X6 = $`; any other symbol is
+//!   numbered from `X7` in code point order and listed in the header.
+//! - Transitions are ordered by state number, then symbol number.
+//!
+//! Decoding names `q1` as `0`, the halt states `halt`, `halt_2`, … and the remaining states
+//! `1`, `2`, … in order, so `encode(decode(s)) == s` for every encoded `s`. With ten or more
+//! halt states their numbers are zero-padded (`halt_02`, …, `halt_10`) so that ordering them
+//! by name keeps their numbering.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use crate::constants::{ANY, BLANK, HALT_PREFIX, LEFT_WALL, RIGHT_WALL, START_STATE};
+use crate::model::Registry;
+use crate::{Direction, Machine, Transition, is_halt_state};
+
+const FIXED_SYMBOLS: [char; 6] = ['0', '1', BLANK, ANY, LEFT_WALL, RIGHT_WALL];
+const DIRECTIONS: [Direction; 3] = [Direction::Left, Direction::Right, Direction::Stay];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    InvalidCharacter(char),
+    MissingHeader,
+    InvalidHeader,
+    InvalidTransition(usize),
+    UnknownSymbol(usize),
+    UnknownDirection(usize),
+    /// The model has no number because it is not in the standard registry.
+    UnsupportedModel(&'static str),
+}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingError::InvalidCharacter(c) => {
+                write!(f, "Invalid character '{c}', only 0 and 1 are allowed")
+            }
+            EncodingError::MissingHeader => write!(f, "Missing '111' after the header"),
+            EncodingError::InvalidHeader => write!(f, "Malformed header"),
+            EncodingError::InvalidTransition(i) => {
+                write!(f, "Transition {i} must have exactly 5 fields")
+            }
+            EncodingError::UnknownSymbol(j) => write!(f, "Symbol X{j} is not defined"),
+            EncodingError::UnknownDirection(m) => write!(f, "Direction D{m} is not defined"),
+            EncodingError::UnsupportedModel(key) => {
+                write!(f, "The '{key}' model has no binary encoding")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+fn symbol_table(machine: &Machine) -> Vec<char> {
+    let extra: BTreeSet<char> = machine
+        .rules
+        .iter()
+        .flat_map(|r| [r.transition.current_symbol, r.transition.new_symbol])
+        .chain(machine.alphabet.iter().copied())
+        .filter(|c| !FIXED_SYMBOLS.contains(c))
+        .collect();
+    FIXED_SYMBOLS.iter().copied().chain(extra).collect()
+}
+
+fn state_table<'m>(machine: &'m Machine, symbols: &HashMap<char, usize>) -> Vec<&'m str> {
+    let mut order: Vec<&str> = vec![&machine.start_state];
+    let mut halts: Vec<&str> = machine
+        .rules
+        .iter()
+        .flat_map(|r| {
+            [
+                r.transition.current_state.as_str(),
+                r.transition.next_state(),
+            ]
+        })
+        .chain(machine.halt_states.iter().map(String::as_str))
+        .filter(|s| is_halt_state(s) && *s != machine.start_state)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    order.append(&mut halts);
+
+    let mut outgoing: HashMap<&str, Vec<&Transition>> = HashMap::new();
+    for rule in &machine.rules {
+        outgoing
+            .entry(&rule.transition.current_state)
+            .or_default()
+            .push(&rule.transition);
+    }
+    let mut queue = VecDeque::from([machine.start_state.as_str()]);
+    while let Some(state) = queue.pop_front() {
+        let mut edges = outgoing.get(state).cloned().unwrap_or_default();
+        edges.sort_by_key(|t| symbols[&t.current_symbol]);
+        for t in edges {
+            let target = t.next_state();
+            if !order.contains(&target) {
+                order.push(target);
+                queue.push_back(target);
+            }
+        }
+    }
+
+    let mut unreachable: Vec<&str> = machine
+        .rules
+        .iter()
+        .flat_map(|r| {
+            [
+                r.transition.current_state.as_str(),
+                r.transition.next_state(),
+            ]
+        })
+        .filter(|s| !order.contains(s))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    unreachable.sort_by_key(|s| (s.len(), *s));
+    order.append(&mut unreachable);
+    order
+}
+
+fn unary(n: usize) -> String {
+    "0".repeat(n)
+}
+
+/// Encodes `machine`, numbering its model as in the [standard](Registry::standard) registry.
+pub fn encode(machine: &Machine) -> Result<String, EncodingError> {
+    encode_with(&Registry::standard(), machine)
+}
+
+/// [`encode`] numbering the model by its position in `registry`.
+pub fn encode_with(registry: &Registry, machine: &Machine) -> Result<String, EncodingError> {
+    let symbols = symbol_table(machine);
+    let symbol_index: HashMap<char, usize> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (c, i + 1))
+        .collect();
+    let states = state_table(machine, &symbol_index);
+    let state_index: HashMap<&str, usize> = states
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| (s, i + 1))
+        .collect();
+    let halt_count = states[1..].iter().filter(|s| is_halt_state(s)).count();
+
+    let model = registry
+        .models()
+        .position(|m| m == machine.model)
+        .ok_or(EncodingError::UnsupportedModel(machine.model.key()))?
+        + 1;
+    let mut header = vec![unary(model), unary(halt_count + 1)];
+    header.extend(
+        symbols[FIXED_SYMBOLS.len()..]
+            .iter()
+            .map(|&c| unary(c as usize)),
+    );
+
+    let mut codes: Vec<(usize, usize, String)> = machine
+        .rules
+        .iter()
+        .map(|rule| {
+            let t = &rule.transition;
+            let i = state_index[t.current_state.as_str()];
+            let j = symbol_index[&t.current_symbol];
+            let k = state_index[t.next_state()];
+            let l = symbol_index[&t.new_symbol];
+            let m = DIRECTIONS.iter().position(|&d| d == t.direction).unwrap() + 1;
+            let code = [i, j, k, l, m].map(unary).join("1");
+            (i, j, code)
+        })
+        .collect();
+    codes.sort();
+
+    Ok(format!(
+        "{}111{}",
+        header.join("1"),
+        codes
+            .into_iter()
+            .map(|(_, _, code)| code)
+            .collect::<Vec<_>>()
+            .join("11")
+    ))
+}
+
+fn state_name(index: usize, halt_count: usize) -> String {
+    match index {
+        1 => START_STATE.to_string(),
+        i if i <= halt_count + 1 && i == 2 => HALT_PREFIX.to_string(),
+        i if i <= halt_count + 1 => {
+            let width = halt_count.to_string().len();
+            format!("{}_{:0width$}", HALT_PREFIX, i - 1)
+        }
+        i => (i - 1 - halt_count).to_string(),
+    }
+}
+
+/// Decodes a machine whose model is numbered as in the [standard](Registry::standard)
+/// registry.
+pub fn decode(encoded: &str) -> Result<Machine, EncodingError> {
+    decode_with(&Registry::standard(), encoded)
+}
+
+/// [`decode`] looking the model number up in `registry`.
+pub fn decode_with(registry: &Registry, encoded: &str) -> Result<Machine, EncodingError> {
+    let bits: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(c) = bits.chars().find(|&c| c != '0' && c != '1') {
+        return Err(EncodingError::InvalidCharacter(c));
+    }
+    let (header, body) = bits.split_once("111").ok_or(EncodingError::MissingHeader)?;
+
+    let fields: Vec<usize> = header.split('1').map(str::len).collect();
+    if fields.len() < 2 || fields.contains(&0) {
+        return Err(EncodingError::InvalidHeader);
+    }
+    let model = registry
+        .models()
+        .nth(fields[0] - 1)
+        .ok_or(EncodingError::InvalidHeader)?;
+    let halt_count = fields[1] - 1;
+    let symbols = FIXED_SYMBOLS
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(fields[2..].iter().map(|&c| char::from_u32(c as u32)))
+        .collect::<Option<Vec<char>>>()
+        .ok_or(EncodingError::InvalidHeader)?;
+
+    let transitions = body
+        .split("11")
+        .filter(|code| !code.is_empty())
+        .enumerate()
+        .map(|(n, code)| {
+            let parts: Vec<usize> = code.split('1').map(str::len).collect();
+            let [i, j, k, l, m] = parts[..] else {
+                return Err(EncodingError::InvalidTransition(n + 1));
+            };
+            if parts.contains(&0) {
+                return Err(EncodingError::InvalidTransition(n + 1));
+            }
+            let symbol = |x: usize| {
+                symbols
+                    .get(x - 1)
+                    .copied()
+                    .ok_or(EncodingError::UnknownSymbol(x))
+            };
+            Ok(Transition {
+                current_state: state_name(i, halt_count),
+                current_symbol: symbol(j)?,
+                new_symbol: symbol(l)?,
+                direction: *DIRECTIONS
+                    .get(m - 1)
+                    .ok_or(EncodingError::UnknownDirection(m))?,
+                new_state: state_name(k, halt_count),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut machine = Machine::from_transitions(model, transitions);
+    machine
+        .halt_states
+        .extend((2..halt_count + 2).map(|i| state_name(i, halt_count)));
+    machine
+        .alphabet
+        .extend(symbols[FIXED_SYMBOLS.len()..].iter().copied());
+    Ok(machine)
+}
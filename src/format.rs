@@ -0,0 +1,161 @@
+//! Canonical layout for text machine files.
+//!
+//! Rules are ordered by state (start state first, `*` last, numbers inside names compared
+//! numerically) and then by read symbol (`*` last). The sort is stable, so duplicate rules
+//! keep their relative order and the one that fires stays first. Columns are aligned and
+//! unchanged symbols and states are written as `*`, as [`Transition`]'s `Display` does.
+//!
+//! [`Transition`]: crate::Transition
+
+use std::cmp::Ordering;
+
+use crate::constants::{
+    ANY, ANY_STATE, BREAKPOINT, INITIAL_INPUT_DIRECTIVE, START_STATE_DIRECTIVE,
+};
+use crate::{Machine, Rule, epilogue};
+
+/// Splits `name` into text and number chunks so that `q2` sorts before `q10`.
+fn natural_key(name: &str) -> Vec<(String, u128)> {
+    let mut key = Vec::new();
+    let mut chars = name.chars().peekable();
+    while chars.peek().is_some() {
+        let text: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_ascii_digit())).collect();
+        let digits: String = std::iter::from_fn(|| chars.next_if(char::is_ascii_digit)).collect();
+        key.push((text, digits.parse().unwrap_or(0)));
+    }
+    key
+}
+
+fn compare_states(machine: &Machine, a: &str, b: &str) -> Ordering {
+    let rank = |state: &str| {
+        if state == machine.start_state {
+            0
+        } else if state == ANY_STATE {
+            2
+        } else {
+            1
+        }
+    };
+    rank(a)
+        .cmp(&rank(b))
+        .then_with(|| natural_key(a).cmp(&natural_key(b)))
+        .then_with(|| a.cmp(b))
+}
+
+fn compare_symbols(a: char, b: char) -> Ordering {
+    (a == ANY).cmp(&(b == ANY)).then(a.cmp(&b))
+}
+
+/// Copy of `machine` with its rules in canonical order.
+pub fn canonical(machine: &Machine) -> Machine {
+    let mut rules: Vec<Rule> = machine.rules.clone();
+    rules.sort_by(|a, b| {
+        let (a, b) = (&a.transition, &b.transition);
+        compare_states(machine, &a.current_state, &b.current_state)
+            .then_with(|| compare_symbols(a.current_symbol, b.current_symbol))
+    });
+    Machine {
+        rules,
+        ..machine.clone()
+    }
+}
+
+/// Comment texts of a machine file, whole-line and inline, skipping the header line and the
+/// directives.
+fn comments(text: &str) -> Vec<&str> {
+    let mut comments: Vec<&str> = text
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| {
+            !line.starts_with(START_STATE_DIRECTIVE) && !line.starts_with(INITIAL_INPUT_DIRECTIVE)
+        })
+        .filter_map(|line| match line.strip_prefix(';') {
+            Some(comment) => Some(comment.trim()),
+            None => line.split_once(" ;").map(|(_, comment)| comment.trim()),
+        })
+        .filter(|comment| !comment.is_empty())
+        .collect();
+    comments.sort_unstable();
+    comments
+}
+
+/// Comments of the machine file `original` that do not appear in `formatted`, its
+/// formatted version. Formatting must never drop any.
+pub fn lost_comments(original: &str, formatted: &str) -> Vec<String> {
+    let mut kept = comments(formatted).into_iter().peekable();
+    let mut lost = Vec::new();
+    for comment in comments(original) {
+        while kept.next_if(|&k| k < comment).is_some() {}
+        if kept.next_if_eq(&comment).is_none() {
+            lost.push(comment.to_string());
+        }
+    }
+    lost
+}
+
+/// Renders `machine` in canonical order with aligned columns.
+pub fn format(machine: &Machine) -> String {
+    let machine = canonical(machine);
+    let columns: Vec<[String; 5]> = machine
+        .rules
+        .iter()
+        .map(|rule| {
+            let text = rule.transition.to_string();
+            let mut parts = text.split(' ').map(str::to_string);
+            std::array::from_fn(|_| parts.next().unwrap_or_default())
+        })
+        .collect();
+    let mut widths = [0; 5];
+    for row in &columns {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let has_annotation = machine
+        .rules
+        .iter()
+        .any(|r| r.breakpoint || r.comment.is_some());
+
+    let mut out = format!("{}\n", machine.model);
+    let body = Machine {
+        rules: Vec::new(),
+        trailing_comments: Vec::new(),
+        ..machine.clone()
+    }
+    .body();
+    out.push_str(&body);
+    for (rule, row) in machine.rules.iter().zip(&columns) {
+        for comment in &rule.detached_comments {
+            out.push_str(&format!(";{comment}\n"));
+        }
+        if !rule.detached_comments.is_empty() {
+            out.push('\n');
+        }
+        for comment in &rule.leading_comments {
+            out.push_str(&format!(";{comment}\n"));
+        }
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            line.push_str(cell);
+            if i < 4 || has_annotation {
+                line.push_str(&" ".repeat(width - cell.chars().count()));
+            }
+        }
+        if rule.breakpoint {
+            line.push_str(&format!(" {BREAKPOINT}"));
+        } else if rule.comment.is_some() && machine.rules.iter().any(|r| r.breakpoint) {
+            line.push_str("  ");
+        }
+        if let Some(comment) = &rule.comment {
+            line.push_str(&format!(" ;{comment}"));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out.push_str(&epilogue(&machine.trailing_comments));
+    out
+}
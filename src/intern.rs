@@ -0,0 +1,84 @@
+//! Compact integer IDs for state names.
+//!
+//! A [`StateTable`] interns every state of a machine once, when it is loaded. Hot loops
+//! such as the simulator then copy and compare [`StateId`]s, and only look the names up
+//! again when printing.
+
+use std::collections::HashMap;
+
+use crate::{Machine, is_halt_state};
+
+/// Index of a state in a [`StateTable`]. It is meaningless without its table, so it is not
+/// serializable; store the state's name instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(u32);
+
+impl StateId {
+    /// Position of the state in its table, usable as an index into per-state vectors.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StateTable {
+    names: Vec<String>,
+    ids: HashMap<String, StateId>,
+    halting: Vec<bool>,
+}
+
+impl StateTable {
+    pub fn new() -> Self {
+        StateTable::default()
+    }
+
+    /// Every state named in `machine`, the start state first and then in order of
+    /// appearance. `*` is interned like any other name.
+    pub fn for_machine(machine: &Machine) -> Self {
+        let mut table = StateTable::new();
+        table.intern(&machine.start_state);
+        for rule in &machine.rules {
+            table.intern(&rule.transition.current_state);
+            table.intern(&rule.transition.new_state);
+        }
+        table
+    }
+
+    /// ID of `name`, adding it to the table if it is new.
+    pub fn intern(&mut self, name: &str) -> StateId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = StateId(u32::try_from(self.names.len()).expect("more than u32::MAX states"));
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.halting.push(is_halt_state(name));
+        id
+    }
+
+    pub fn get(&self, name: &str) -> Option<StateId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: StateId) -> &str {
+        &self.names[id.index()]
+    }
+
+    /// Whether `id` is a halt state, decided once when it was interned.
+    pub fn is_halt(&self, id: StateId) -> bool {
+        self.halting[id.index()]
+    }
+
+    /// Every ID in the table, in the order the states were interned.
+    pub fn ids(&self) -> impl Iterator<Item = StateId> + use<> {
+        (0..self.names.len() as u32).map(StateId)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,69 @@
+//! Catches Ctrl-C so that a long run can save its progress before it stops.
+//!
+//! On Unix a SIGINT handler only sets a flag, which the run polls between slices of steps,
+//! whether or not standard input is a terminal. Elsewhere Ctrl-C ends the process as usual.
+
+#[cfg(unix)]
+use std::ffi::c_int;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Turns Ctrl-C into a flag for as long as it is alive, then restores the previous handler.
+pub struct Interrupt {
+    #[cfg(unix)]
+    previous: usize,
+}
+
+impl Interrupt {
+    pub fn catch() -> Self {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        #[cfg(unix)]
+        {
+            // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+            let previous = unsafe {
+                sys::signal(
+                    sys::SIGINT,
+                    sys::on_interrupt as extern "C" fn(c_int) as usize,
+                )
+            };
+            Interrupt { previous }
+        }
+        #[cfg(not(unix))]
+        Interrupt {}
+    }
+
+    /// Whether Ctrl-C was pressed since [`catch`](Self::catch).
+    pub fn is_set(&self) -> bool {
+        INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Interrupt {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if self.previous != sys::SIG_ERR {
+            // SAFETY: `previous` is the handler `signal` returned, so it is valid to restore.
+            unsafe { sys::signal(sys::SIGINT, self.previous) };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+    use std::sync::atomic::Ordering;
+
+    pub const SIGINT: c_int = 2;
+    pub const SIG_ERR: usize = usize::MAX;
+
+    unsafe extern "C" {
+        /// `handler` and the result are `sighandler_t`: a function pointer, or `SIG_DFL`,
+        /// `SIG_IGN` or `SIG_ERR`.
+        pub fn signal(signum: c_int, handler: usize) -> usize;
+    }
+
+    pub extern "C" fn on_interrupt(_: c_int) {
+        super::INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,639 @@
+//! JSON representation of a [`Machine`].
+//!
+//! The schema is a single object:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "model": "infinite",
+//!   "start_state": "0",
+//!   "halt_states": ["halt"],
+//!   "alphabet": ["0", "1", "_"],
+//!   "transitions": [
+//!     { "from": "0", "read": "0", "write": "1", "move": "r", "to": "0", "line": 15 }
+//!   ]
+//! }
+//! ```
+//!
+//! - `model` is the key of a model in the standard registry, `"infinite"` or `"sipser"`.
+//! - `read` and `write` are single-character strings; `*` keeps its text-format meaning
+//!   (wildcard when read, "leave unchanged" when written).
+//! - `move` uses the text-format letters `l`, `r` and `*`.
+//! - `to` may be `*`, meaning "stay in the current state".
+//! - `line` is the 1-based source line of the transition, or `null` for generated ones.
+//! - Optional transition fields: `breakpoint` (`true` for a `!` rule), `comment` (the inline
+//!   comment), `leading_comments` (comment lines directly above the rule) and
+//!   `detached_comments` (comment lines above those, separated by a blank line).
+//! - Optional top-level fields: `initial_input`, `comments` (the header comment block) and
+//!   `trailing_comments` (comment lines after the last rule).
+//! - `halt_states` and `alphabet` may list extra entries, but are always merged with the
+//!   halt states and symbols the transitions use, so a machine stays consistent with its rules.
+//!   Halting is decided by the state name everywhere, so every declared halt state must start
+//!   with `halt`.
+//!
+//! Comment strings hold the text after the `;`, including any leading space.
+//!
+//! Fields are written verbatim, so JSON → JSON is lossless, and text → JSON → text keeps every
+//! rule, comment, breakpoint and directive. The layout of the text is not kept: a rule that
+//! stays in its state or writes the symbol it reads comes back with `*`, and the only blank
+//! lines written are the ones before a rule's leading comments and before the trailing
+//! comments.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use crate::constants::HALT_PREFIX;
+use crate::model::Registry;
+use crate::{Direction, Machine, Rule, Transition, is_halt_state};
+
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Maximum depth of nested arrays and objects, so deeply nested input fails with an error
+/// rather than overflowing the stack.
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct JsonError {
+    /// Line and column of a syntax error. Schema errors, found once the document has been
+    /// parsed, have none.
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{line}:{column}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) if n.fract() == 0.0 && *n >= 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Number(n) if n.fract() == 0.0 => Some(*n as i64),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_compact(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_compact(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            out.push_str("{ ");
+            for (i, (key, item)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_string(out, key);
+                out.push_str(": ");
+                write_compact(out, item);
+            }
+            out.push_str(" }");
+        }
+    }
+}
+
+/// Pretty-prints objects one field per line and arrays of objects one element per line,
+/// keeping scalar arrays and nested objects on a single line. This keeps machine files
+/// diff-friendly.
+fn write_pretty(out: &mut String, value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent + 1);
+    let close = "  ".repeat(indent);
+    match value {
+        Value::Array(items) if items.iter().any(|v| matches!(v, Value::Object(_))) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&pad);
+                write_compact(out, item);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&close);
+            out.push(']');
+        }
+        Value::Object(fields) if indent == 0 => {
+            out.push_str("{\n");
+            for (i, (key, item)) in fields.iter().enumerate() {
+                out.push_str(&pad);
+                write_string(out, key);
+                out.push_str(": ");
+                write_pretty(out, item, indent + 1);
+                out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&close);
+            out.push('}');
+        }
+        _ => write_compact(out, value),
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        write_pretty(&mut out, self, 0);
+        write!(f, "{out}")
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    /// Arrays and objects currently open.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> JsonError {
+        JsonError {
+            position: Some((self.line, self.column)),
+            message: message.into(),
+        }
+    }
+
+    /// Enters an array or object, failing past [`MAX_DEPTH`].
+    fn descend(&mut self) -> Result<(), JsonError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(format!("nesting deeper than {MAX_DEPTH} levels")));
+        }
+        Ok(())
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{expected}', found '{c}'"))),
+            None => Err(self.error(format!("expected '{expected}', found end of input"))),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str, value: Value) -> Result<Value, JsonError> {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('{') => {
+                self.descend()?;
+                let object = self.parse_object();
+                self.depth -= 1;
+                object
+            }
+            Some('[') => {
+                self.descend()?;
+                let array = self.parse_array();
+                self.depth -= 1;
+                array
+            }
+            Some('"') => self.parse_string().map(Value::String),
+            Some('t') => self.expect_keyword("true", Value::Bool(true)),
+            Some('f') => self.expect_keyword("false", Value::Bool(false)),
+            Some('n') => self.expect_keyword("null", Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(c) => Err(self.error(format!("unexpected character '{c}'"))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Value, JsonError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.bump();
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(fields)),
+                _ => return Err(self.error("expected ',' or '}' in object")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, JsonError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.bump();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error(format!("invalid unicode escape '{hex}'")))?;
+                        s.push(c);
+                    }
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, JsonError> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        text.parse::<f64>()
+            .map(Value::Number)
+            .map_err(|_| self.error(format!("invalid number '{text}'")))
+    }
+}
+
+pub fn parse(input: &str) -> Result<Value, JsonError> {
+    let mut parser = Parser::new(input);
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err(parser.error("trailing characters after JSON value"));
+    }
+    Ok(value)
+}
+
+fn string(s: impl Into<String>) -> Value {
+    Value::String(s.into())
+}
+
+pub fn machine_to_json(machine: &Machine) -> Value {
+    let transitions = machine
+        .rules
+        .iter()
+        .map(|rule| {
+            let t = &rule.transition;
+            let mut fields = vec![
+                ("from".to_string(), string(t.current_state.clone())),
+                ("read".to_string(), string(t.current_symbol)),
+                ("write".to_string(), string(t.new_symbol)),
+                ("move".to_string(), string(t.direction.to_string())),
+                ("to".to_string(), string(t.new_state.clone())),
+                (
+                    "line".to_string(),
+                    rule.line.map_or(Value::Null, |l| Value::Number(l as f64)),
+                ),
+            ];
+            if rule.breakpoint {
+                fields.push(("breakpoint".to_string(), Value::Bool(true)));
+            }
+            if let Some(comment) = &rule.comment {
+                fields.push(("comment".to_string(), string(comment.clone())));
+            }
+            if !rule.leading_comments.is_empty() {
+                fields.push((
+                    "leading_comments".to_string(),
+                    string_array(&rule.leading_comments),
+                ));
+            }
+            if !rule.detached_comments.is_empty() {
+                fields.push((
+                    "detached_comments".to_string(),
+                    string_array(&rule.detached_comments),
+                ));
+            }
+            Value::Object(fields)
+        })
+        .collect();
+
+    let mut document = Value::Object(vec![
+        ("version".to_string(), Value::Number(SCHEMA_VERSION as f64)),
+        ("model".to_string(), string(machine.model.key())),
+        (
+            "start_state".to_string(),
+            string(machine.start_state.clone()),
+        ),
+        (
+            "halt_states".to_string(),
+            Value::Array(
+                machine
+                    .halt_states
+                    .iter()
+                    .cloned()
+                    .map(Value::String)
+                    .collect(),
+            ),
+        ),
+        (
+            "alphabet".to_string(),
+            Value::Array(machine.alphabet.iter().map(|&c| string(c)).collect()),
+        ),
+        ("transitions".to_string(), Value::Array(transitions)),
+    ]);
+    if let Value::Object(fields) = &mut document {
+        if let Some(input) = &machine.initial_input {
+            fields.push(("initial_input".to_string(), string(input.clone())));
+        }
+        if !machine.comments.is_empty() {
+            fields.push(("comments".to_string(), string_array(&machine.comments)));
+        }
+        if !machine.trailing_comments.is_empty() {
+            fields.push((
+                "trailing_comments".to_string(),
+                string_array(&machine.trailing_comments),
+            ));
+        }
+    }
+    document
+}
+
+fn string_array(items: &[String]) -> Value {
+    Value::Array(items.iter().cloned().map(Value::String).collect())
+}
+
+fn schema_error(message: impl Into<String>) -> JsonError {
+    JsonError {
+        position: None,
+        message: message.into(),
+    }
+}
+
+fn field<'v>(value: &'v Value, key: &str) -> Result<&'v Value, JsonError> {
+    value
+        .get(key)
+        .ok_or_else(|| schema_error(format!("missing field \"{key}\"")))
+}
+
+fn str_field<'v>(value: &'v Value, key: &str) -> Result<&'v str, JsonError> {
+    field(value, key)?
+        .as_str()
+        .ok_or_else(|| schema_error(format!("field \"{key}\" must be a string")))
+}
+
+fn char_field(value: &Value, key: &str) -> Result<char, JsonError> {
+    single_char(str_field(value, key)?)
+        .ok_or_else(|| schema_error(format!("field \"{key}\" must be a single character")))
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn optional_str_field(value: &Value, key: &str) -> Result<Option<String>, JsonError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(_) => str_field(value, key).map(|s| Some(s.to_string())),
+    }
+}
+
+fn string_array_field(value: &Value, key: &str) -> Result<Vec<String>, JsonError> {
+    if value.get(key).is_none() {
+        return Ok(Vec::new());
+    }
+    array_field(value, key)?
+        .iter()
+        .map(|s| {
+            s.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| schema_error(format!("\"{key}\" entries must be strings")))
+        })
+        .collect()
+}
+
+fn array_field<'v>(value: &'v Value, key: &str) -> Result<&'v [Value], JsonError> {
+    field(value, key)?
+        .as_array()
+        .ok_or_else(|| schema_error(format!("field \"{key}\" must be an array")))
+}
+
+/// Reads a machine whose model is a key of the [standard](Registry::standard) registry.
+pub fn machine_from_json(value: &Value) -> Result<Machine, JsonError> {
+    machine_from_json_with(&Registry::standard(), value)
+}
+
+/// [`machine_from_json`] looking the model key up in `registry`.
+pub fn machine_from_json_with(registry: &Registry, value: &Value) -> Result<Machine, JsonError> {
+    if let Some(version) = value.get("version")
+        && version.as_u64() != Some(SCHEMA_VERSION)
+    {
+        return Err(schema_error(format!(
+            "unsupported schema version, expected {SCHEMA_VERSION}"
+        )));
+    }
+    let key = str_field(value, "model")?;
+    let model = registry
+        .find(key)
+        .ok_or_else(|| schema_error(format!("unknown model \"{key}\"")))?;
+
+    let rules = array_field(value, "transitions")?
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let direction = str_field(t, "move")?
+                .parse::<Direction>()
+                .map_err(|e| schema_error(format!("transition {i}: {e}")))?;
+            let line = match t.get("line") {
+                None | Some(Value::Null) => None,
+                Some(l) => Some(l.as_u64().ok_or_else(|| {
+                    schema_error(format!(
+                        "transition {i}: \"line\" must be a positive integer"
+                    ))
+                })? as usize),
+            };
+            Ok(Rule {
+                line,
+                breakpoint: matches!(t.get("breakpoint"), Some(Value::Bool(true))),
+                comment: optional_str_field(t, "comment")?,
+                leading_comments: string_array_field(t, "leading_comments")?,
+                detached_comments: string_array_field(t, "detached_comments")?,
+                ..Rule::new(Transition {
+                    current_state: str_field(t, "from")?.to_string(),
+                    current_symbol: char_field(t, "read")?,
+                    new_symbol: char_field(t, "write")?,
+                    direction,
+                    new_state: str_field(t, "to")?.to_string(),
+                })
+            })
+        })
+        .collect::<Result<Vec<_>, JsonError>>()?;
+
+    let mut machine = Machine::new(model, rules);
+    machine.initial_input = optional_str_field(value, "initial_input")?;
+    machine.comments = string_array_field(value, "comments")?;
+    machine.trailing_comments = string_array_field(value, "trailing_comments")?;
+    if let Some(start) = value.get("start_state") {
+        machine.start_state = start
+            .as_str()
+            .ok_or_else(|| schema_error("field \"start_state\" must be a string"))?
+            .to_string();
+    }
+    // The declared sets may add states and symbols, but never drop the ones the rules use.
+    if value.get("halt_states").is_some() {
+        let declared = array_field(value, "halt_states")?
+            .iter()
+            .map(|s| {
+                let state = s
+                    .as_str()
+                    .ok_or_else(|| schema_error("\"halt_states\" entries must be strings"))?;
+                if !is_halt_state(state) {
+                    return Err(schema_error(format!(
+                        "halt state \"{state}\" must start with \"{HALT_PREFIX}\", which is how \
+                         every tool recognizes halt states"
+                    )));
+                }
+                Ok(state.to_string())
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        machine.halt_states.extend(declared);
+    }
+    if value.get("alphabet").is_some() {
+        let declared = array_field(value, "alphabet")?
+            .iter()
+            .map(|s| {
+                s.as_str()
+                    .and_then(single_char)
+                    .ok_or_else(|| schema_error("\"alphabet\" entries must be single characters"))
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        machine.alphabet.extend(declared);
+    }
+    Ok(machine)
+}
@@ -0,0 +1,1443 @@
+//! Parsing, conversion and inspection of Turing machines written in the Morphett text
+//! format.
+//!
+//! A [`Machine`] is read with [`parse_machine`] (text), [`parse_input`] (any supported
+//! format) or [`read_machine`] (from a file), converted between the doubly infinite tape
+//! model and Sipser's left-bounded tape with [`convert`] or [`convert_to`], and written back
+//! with its `Display` impl or [`export_machine`]. The available models and conversions
+//! live in a [`model::Registry`]; [`convert_with`] converts through a custom one. The
+//! `turing` binary is a command-line front end over this crate.
+
+#![allow(clippy::module_name_repetitions)]
+
+pub mod batch;
+pub mod builder;
+pub mod busy_beaver;
+pub mod dense;
+pub mod diagnostics;
+pub mod diff;
+pub mod encoding;
+pub mod format;
+pub mod intern;
+pub mod json;
+pub mod lint;
+pub mod mermaid;
+pub mod model;
+pub mod simulator;
+pub mod snapshot;
+pub mod stats;
+pub mod stream;
+pub mod table;
+pub mod tape;
+pub mod verify;
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use intern::{StateId, StateTable};
+use model::RewrittenRule;
+
+/// Anything that can go wrong reading, converting or writing a machine.
+#[derive(Debug)]
+pub enum ConversionError {
+    Io(std::io::Error),
+    InvalidHeader(String),
+    ParseError(Vec<diagnostics::Diagnostic>),
+    Json(json::JsonError),
+    BusyBeaver(busy_beaver::BusyBeaverError),
+    Encoding(encoding::EncodingError),
+    /// No registered conversion, or chain of conversions, leads from `from` to `to`.
+    NoConversion {
+        from: MachineType,
+        to: Option<MachineType>,
+    },
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::Io(e) => write!(f, "I/O error: {e}"),
+            ConversionError::InvalidHeader(s) => write!(f, "Invalid machine type header: {s}"),
+            ConversionError::ParseError(diagnostics) => {
+                write!(f, "{}", diagnostics::render(diagnostics))
+            }
+            ConversionError::Json(e) => write!(f, "Invalid JSON machine: {e}"),
+            ConversionError::BusyBeaver(e) => write!(f, "Busy beaver notation: {e}"),
+            ConversionError::Encoding(e) => write!(f, "Invalid machine encoding: {e}"),
+            ConversionError::NoConversion { from, to: Some(to) } => {
+                write!(f, "No conversion from {} to {}", from.key(), to.key())
+            }
+            ConversionError::NoConversion { from, to: None } => {
+                write!(f, "No conversion from {}", from.key())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConversionError::Io(e) => Some(e),
+            ConversionError::Json(e) => Some(e),
+            ConversionError::BusyBeaver(e) => Some(e),
+            ConversionError::Encoding(e) => Some(e),
+            ConversionError::InvalidHeader(_)
+            | ConversionError::ParseError(_)
+            | ConversionError::NoConversion { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConversionError {
+    fn from(err: std::io::Error) -> Self {
+        ConversionError::Io(err)
+    }
+}
+
+impl From<json::JsonError> for ConversionError {
+    fn from(err: json::JsonError) -> Self {
+        ConversionError::Json(err)
+    }
+}
+
+impl From<busy_beaver::BusyBeaverError> for ConversionError {
+    fn from(err: busy_beaver::BusyBeaverError) -> Self {
+        ConversionError::BusyBeaver(err)
+    }
+}
+
+impl From<encoding::EncodingError> for ConversionError {
+    fn from(err: encoding::EncodingError) -> Self {
+        ConversionError::Encoding(err)
+    }
+}
+
+/// Why a single transition line could not be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseTransitionError {
+    Empty,
+    InvalidPartCount(usize),
+    InvalidDirection(String),
+    InvalidSymbol(String),
+}
+
+impl Display for ParseTransitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseTransitionError::Empty => write!(f, "Line is empty"),
+            ParseTransitionError::InvalidPartCount(count) => {
+                write!(f, "Invalid number of parts, expected 5, got {count}")
+            }
+            ParseTransitionError::InvalidDirection(dir) => {
+                write!(f, "Invalid direction: {dir}, expected l, r or *")?;
+                match Direction::suggest(dir) {
+                    Some(d) => write!(f, " (did you mean '{d}'?)"),
+                    None => Ok(()),
+                }
+            }
+            ParseTransitionError::InvalidSymbol(sym) => {
+                write!(f, "Invalid symbol, must be a single char: '{sym}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseTransitionError {}
+
+/// How the text parser treats malformed symbol and direction tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject multi-character symbols and anything but `l`, `r` and `*` as a direction.
+    #[default]
+    Strict,
+    /// Legacy behaviour: keep the first character of each symbol and accept `L` and `R`.
+    Lenient,
+}
+
+/// A [`ParseTransitionError`] with the byte range of the offending text in its line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineError {
+    pub error: ParseTransitionError,
+    pub span: Range<usize>,
+}
+
+pub mod constants {
+    pub const LEFT_WALL: char = '#';
+    pub const RIGHT_WALL: char = '$';
+    pub const BLANK: char = '_';
+    pub const ANY: char = '*';
+    /// [`ANY`] as a state name, to compare against without allocating.
+    pub const ANY_STATE: &str = "*";
+    pub const HALT_PREFIX: &str = "halt";
+    pub const SIM_PREFIX: &str = "sim_";
+    pub const START_STATE: &str = "0";
+    pub const START_STATE_DIRECTIVE: &str = "; Start state:";
+    pub const INITIAL_INPUT_DIRECTIVE: &str = "; Initial input:";
+    pub const BREAKPOINT: char = '!';
+    pub const TO_SIPSER_BANNER: &str = "; --- Infinite-to-Sipser Simulation ---";
+    pub const TO_INFINITE_BANNER: &str = "; --- Sipser-to-Infinite Simulation ---";
+}
+use constants::*;
+
+/// Head movement of a transition: `l`, `r` or `*` (stay).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Left,
+    Right,
+    Stay,
+}
+
+impl FromStr for Direction {
+    type Err = ParseTransitionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l" => Ok(Direction::Left),
+            "r" => Ok(Direction::Right),
+            "*" => Ok(Direction::Stay),
+            _ => Err(ParseTransitionError::InvalidDirection(s.to_string())),
+        }
+    }
+}
+
+impl Direction {
+    /// The direction a misspelled token most likely meant, for error messages.
+    fn suggest(token: &str) -> Option<Direction> {
+        match token.to_ascii_lowercase().as_str() {
+            "l" | "left" | "<" => Some(Direction::Left),
+            "r" | "right" | ">" => Some(Direction::Right),
+            "s" | "n" | "stay" | "none" | "-" => Some(Direction::Stay),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Left => write!(f, "l"),
+            Direction::Right => write!(f, "r"),
+            Direction::Stay => write!(f, "*"),
+        }
+    }
+}
+
+/// Identifies a tape model, such as the doubly infinite tape (`;I`) or the tape bounded on
+/// the left as in Sipser (`;S`). Models are open: a new one is a
+/// [`TapeModel`](model::TapeModel) wrapped with [`MachineType::new`] and registered with its
+/// conversions in a [`Registry`](model::Registry). Two machine types are equal when their
+/// models have the same key.
+#[derive(Clone, Copy)]
+pub struct MachineType(&'static dyn model::TapeModel);
+
+// Named like the enum variants they replaced, so paths keep working.
+#[allow(non_upper_case_globals)]
+impl MachineType {
+    pub const Infinite: MachineType = MachineType::new(&model::Infinite);
+    pub const Sipser: MachineType = MachineType::new(&model::Sipser);
+
+    pub const fn new(model: &'static dyn model::TapeModel) -> Self {
+        MachineType(model)
+    }
+
+    pub fn model(self) -> &'static dyn model::TapeModel {
+        self.0
+    }
+
+    /// Lower-case name used on the command line and in JSON files, e.g. `sipser`.
+    pub fn key(self) -> &'static str {
+        self.0.key()
+    }
+
+    /// First line of a machine file in this model, e.g. `;S`.
+    pub fn header(self) -> &'static str {
+        self.0.header()
+    }
+
+    /// Whether a left move on the first cell leaves the head in place.
+    pub fn bounded_left(self) -> bool {
+        self.0.bounded_left()
+    }
+}
+
+impl PartialEq for MachineType {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for MachineType {}
+
+impl std::hash::Hash for MachineType {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl std::fmt::Debug for MachineType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MachineType").field(&self.key()).finish()
+    }
+}
+
+impl FromStr for MachineType {
+    type Err = ConversionError;
+    /// The model of the [standard](model::Registry::standard) registry whose header or
+    /// banner is `s`. Use [`Registry::from_header`](model::Registry::from_header) for other
+    /// registries.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        model::Registry::standard()
+            .from_header(s)
+            .ok_or_else(|| ConversionError::InvalidHeader(s.to_string()))
+    }
+}
+
+impl Display for MachineType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.header())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MachineType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.key())
+    }
+}
+
+/// Deserializes the key of a model in the [standard](model::Registry::standard) registry.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MachineType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = <String as serde::Deserialize>::deserialize(deserializer)?;
+        model::Registry::standard()
+            .find(&key)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown model \"{key}\"")))
+    }
+}
+
+/// Output formats accepted by [`export_machine`] and [`convert_to`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Text,
+    Json,
+    BusyBeaver,
+    Binary,
+    Mermaid { collapse: bool },
+    Markdown,
+    Html,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ExportFormat::Text),
+            "json" => Ok(ExportFormat::Json),
+            "bb" => Ok(ExportFormat::BusyBeaver),
+            "binary" => Ok(ExportFormat::Binary),
+            "mermaid" => Ok(ExportFormat::Mermaid { collapse: false }),
+            "mermaid-collapsed" => Ok(ExportFormat::Mermaid { collapse: true }),
+            "table" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(format!(
+                "Unknown export format: {s} (expected text, json, bb, binary, mermaid, mermaid-collapsed, table or html)"
+            )),
+        }
+    }
+}
+
+/// One `state read write direction next` rule. A `*` written symbol or next state means
+/// "unchanged", and a `*` state or read symbol matches anything.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    pub current_state: String,
+    pub current_symbol: char,
+    pub new_symbol: char,
+    pub direction: Direction,
+    pub new_state: String,
+}
+
+impl Transition {
+    pub fn new(
+        current_state: impl Into<String>,
+        current_symbol: char,
+        new_symbol: char,
+        direction: Direction,
+        new_state: impl Into<String>,
+    ) -> Self {
+        Transition {
+            current_state: current_state.into(),
+            current_symbol,
+            new_symbol,
+            direction,
+            new_state: new_state.into(),
+        }
+    }
+
+    /// The state the rule moves to, with a `*` next state resolved to the current state.
+    /// For a rule whose current state is `*` that is still `*`; see [`applied`](Self::applied).
+    pub fn next_state(&self) -> &str {
+        if self.new_state == ANY_STATE {
+            &self.current_state
+        } else {
+            &self.new_state
+        }
+    }
+
+    /// The symbol the rule writes, with a `*` resolved to the symbol read. For a rule that
+    /// reads `*` that is still `*`; see [`applied`](Self::applied).
+    pub fn written_symbol(&self) -> char {
+        if self.new_symbol == ANY {
+            self.current_symbol
+        } else {
+            self.new_symbol
+        }
+    }
+
+    /// The rule as it fires in `state` reading `symbol`, which it must match: a `*` current
+    /// state or read symbol is replaced by them, so that [`next_state`](Self::next_state) and
+    /// [`written_symbol`](Self::written_symbol) are never wildcards.
+    pub fn applied(&self, state: &str, symbol: char) -> Transition {
+        Transition {
+            current_state: state.to_string(),
+            current_symbol: symbol,
+            ..self.clone()
+        }
+    }
+}
+
+/// Writes a rule in text form, with an unchanged symbol or state written as `*`.
+fn write_transition(
+    f: &mut Formatter<'_>,
+    current_state: &str,
+    current_symbol: char,
+    new_symbol: char,
+    direction: Direction,
+    new_state: &str,
+) -> std::fmt::Result {
+    let new_symbol = if new_symbol == current_symbol {
+        ANY
+    } else {
+        new_symbol
+    };
+    let new_state = if new_state == current_state {
+        ANY_STATE
+    } else {
+        new_state
+    };
+    write!(
+        f,
+        "{current_state} {current_symbol} {new_symbol} {direction} {new_state}"
+    )
+}
+
+impl Display for Transition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_transition(
+            f,
+            &self.current_state,
+            self.current_symbol,
+            self.new_symbol,
+            self.direction,
+            &self.new_state,
+        )
+    }
+}
+
+impl Display for RewrittenRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_transition(
+            f,
+            &self.current_state,
+            self.current_symbol,
+            self.new_symbol,
+            self.direction,
+            &self.new_state,
+        )
+    }
+}
+
+impl From<RewrittenRule> for Transition {
+    fn from(rule: RewrittenRule) -> Self {
+        Transition::new(
+            &*rule.current_state,
+            rule.current_symbol,
+            rule.new_symbol,
+            rule.direction,
+            &*rule.new_state,
+        )
+    }
+}
+
+/// A transition together with what the source file said around it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    pub transition: Transition,
+    pub line: Option<usize>,
+    pub breakpoint: bool,
+    pub comment: Option<String>,
+    /// Comment lines directly above the rule.
+    pub leading_comments: Vec<String>,
+    /// Comment lines above `leading_comments`, separated from them by a blank line.
+    pub detached_comments: Vec<String>,
+}
+
+impl Rule {
+    pub fn new(transition: Transition) -> Self {
+        Rule {
+            transition,
+            line: None,
+            breakpoint: false,
+            comment: None,
+            leading_comments: Vec::new(),
+            detached_comments: Vec::new(),
+        }
+    }
+
+    /// `transition` carrying over the line, breakpoint and comments of `source`.
+    pub fn annotated_like(transition: Transition, source: &Rule) -> Self {
+        Rule {
+            transition,
+            ..source.clone()
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Annotated(self, &self.transition).fmt(f)
+    }
+}
+
+/// A transition printed with the comments and breakpoint of a rule, as [`Rule`] prints its
+/// own transition.
+struct Annotated<'r, T>(&'r Rule, T);
+
+impl<T: Display> Display for Annotated<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Annotated(rule, transition) = self;
+        for comment in &rule.detached_comments {
+            writeln!(f, ";{comment}")?;
+        }
+        if !rule.detached_comments.is_empty() {
+            writeln!(f)?;
+        }
+        for comment in &rule.leading_comments {
+            writeln!(f, ";{comment}")?;
+        }
+        write!(f, "{transition}")?;
+        if rule.breakpoint {
+            write!(f, " {BREAKPOINT}")?;
+        }
+        if let Some(comment) = &rule.comment {
+            write!(f, " ;{comment}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed machine: its model, start state and rules in file order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Machine {
+    pub model: MachineType,
+    pub start_state: String,
+    pub halt_states: BTreeSet<String>,
+    pub alphabet: BTreeSet<char>,
+    pub rules: Vec<Rule>,
+    pub comments: Vec<String>,
+    /// Comment lines after the last rule.
+    pub trailing_comments: Vec<String>,
+    pub initial_input: Option<String>,
+    /// Non-fatal problems found while parsing the source file. Not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub warnings: Vec<diagnostics::Diagnostic>,
+}
+
+impl Machine {
+    pub fn new(model: MachineType, rules: Vec<Rule>) -> Self {
+        let halt_states = rules
+            .iter()
+            .flat_map(|r| [&r.transition.current_state, &r.transition.new_state])
+            .filter(|s| is_halt_state(s))
+            .cloned()
+            .collect();
+        let alphabet = rules
+            .iter()
+            .flat_map(|r| [r.transition.current_symbol, r.transition.new_symbol])
+            .filter(|&c| c != ANY)
+            .collect();
+        Machine {
+            model,
+            start_state: START_STATE.to_string(),
+            halt_states,
+            alphabet,
+            rules,
+            comments: Vec::new(),
+            trailing_comments: Vec::new(),
+            initial_input: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn builder(model: MachineType) -> builder::MachineBuilder {
+        builder::MachineBuilder::new(model)
+    }
+
+    pub fn from_transitions(model: MachineType, transitions: Vec<Transition>) -> Self {
+        Machine::new(model, transitions.into_iter().map(Rule::new).collect())
+    }
+
+    /// Every state mentioned, with `*` next states resolved to the current state.
+    pub fn states(&self) -> BTreeSet<&str> {
+        std::iter::once(self.start_state.as_str())
+            .chain(self.rules.iter().flat_map(|r| {
+                let t = &r.transition;
+                [t.current_state.as_str(), t.next_state()]
+            }))
+            .collect()
+    }
+
+    pub fn transitions(&self) -> Vec<Transition> {
+        self.rules.iter().map(|r| r.transition.clone()).collect()
+    }
+
+    /// The file contents after the model header line.
+    pub fn body(&self) -> String {
+        let mut out = preamble(
+            &self.start_state,
+            self.initial_input.as_deref(),
+            &self.comments,
+        );
+        for rule in &self.rules {
+            out.push_str(&format!("{rule}\n"));
+        }
+        out.push_str(&epilogue(&self.trailing_comments));
+        out
+    }
+}
+
+/// Directives and header comments that open the body of a machine file.
+fn preamble(start_state: &str, initial_input: Option<&str>, comments: &[String]) -> String {
+    let mut out = String::new();
+    if start_state != START_STATE {
+        out.push_str(&format!("{} {}\n", START_STATE_DIRECTIVE, start_state));
+    }
+    if let Some(input) = initial_input {
+        out.push_str(&format!("{} {}\n", INITIAL_INPUT_DIRECTIVE, input));
+    }
+    for comment in comments {
+        out.push_str(&format!(";{comment}\n"));
+    }
+    if !comments.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Comments that close the body of a machine file, after a blank line.
+fn epilogue(trailing_comments: &[String]) -> String {
+    let mut out = String::new();
+    if !trailing_comments.is_empty() {
+        out.push('\n');
+    }
+    for comment in trailing_comments {
+        out.push_str(&format!(";{comment}\n"));
+    }
+    out
+}
+
+impl Display for Machine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.model)?;
+        write!(f, "{}", self.body())
+    }
+}
+
+#[inline]
+pub fn is_halt_state(state: &str) -> bool {
+    state.starts_with(HALT_PREFIX)
+}
+
+/// `original_new_state` if it halts, otherwise the check state `{check_prefix}{state}`.
+fn get_next_state(original_new_state: &str, check_prefix: &str) -> String {
+    if is_halt_state(original_new_state) {
+        original_new_state.to_string()
+    } else {
+        format!("{check_prefix}{original_new_state}")
+    }
+}
+
+/// Whitespace-separated words of `text` with their byte ranges.
+fn tokens(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push((s..i, &text[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s..text.len(), &text[s..]));
+    }
+    tokens
+}
+
+/// Parses the transition in `line`, ignoring any comment. Error spans index into `line`.
+pub fn parse_line(line: &str, mode: ParseMode) -> Result<Transition, LineError> {
+    let code = line.split(';').next().unwrap_or("");
+    let parts = tokens(code);
+    let fail = |error, span| Err(LineError { error, span });
+    match parts.len() {
+        0 => return fail(ParseTransitionError::Empty, 0..line.len()),
+        5 => {}
+        n if n > 5 => {
+            return fail(
+                ParseTransitionError::InvalidPartCount(n),
+                parts[5].0.start..parts[n - 1].0.end,
+            );
+        }
+        n => {
+            return fail(
+                ParseTransitionError::InvalidPartCount(n),
+                parts[n - 1].0.clone(),
+            );
+        }
+    }
+    let symbol = |(span, text): &(Range<usize>, &str)| {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next(), mode) {
+            (Some(c), None, _) | (Some(c), Some(_), ParseMode::Lenient) => Ok(c),
+            _ => Err(LineError {
+                error: ParseTransitionError::InvalidSymbol(text.to_string()),
+                span: span.clone(),
+            }),
+        }
+    };
+    let (direction_span, direction) = &parts[3];
+    let direction = match mode {
+        ParseMode::Strict => direction.to_string(),
+        ParseMode::Lenient => direction.to_ascii_lowercase(),
+    };
+    Ok(Transition {
+        current_state: parts[0].1.to_string(),
+        current_symbol: symbol(&parts[1])?,
+        new_symbol: symbol(&parts[2])?,
+        direction: direction.parse::<Direction>().map_err(|error| LineError {
+            error,
+            span: direction_span.clone(),
+        })?,
+        new_state: parts[4].1.to_string(),
+    })
+}
+
+/// Parses one rule line, including a trailing `!` breakpoint and `;` comment.
+pub fn parse_rule(line: &str, line_number: usize, mode: ParseMode) -> Result<Rule, LineError> {
+    let (code, comment) = match line.split_once(';') {
+        Some((code, comment)) => (code, Some(comment.to_string())),
+        None => (line, None),
+    };
+    // Only trim the end, so that error spans still index into `line`.
+    let code = code.trim_end();
+    let (code, breakpoint) = match code.strip_suffix(BREAKPOINT) {
+        Some(code) => (code, true),
+        None => (code, false),
+    };
+    Ok(Rule {
+        line: Some(line_number),
+        breakpoint,
+        comment,
+        ..Rule::new(parse_line(code, mode)?)
+    })
+}
+
+fn rename_original_state(state: &str, prefix: &str) -> String {
+    if is_halt_state(state) {
+        state.to_string()
+    } else {
+        format!("{}{}", prefix, state)
+    }
+}
+
+fn generate_carry_logic(carry_0_state: &str, carry_1_state: &str) -> Vec<Transition> {
+    tm![
+        (carry_0_state) '0' => '0', R, (carry_0_state);
+        (carry_0_state) '1' => '0', R, (carry_1_state);
+        (carry_1_state) '0' => '1', R, (carry_0_state);
+        (carry_1_state) '1' => '1', R, (carry_1_state);
+    ]
+}
+
+fn generate_return_head_logic(return_state: &str, target_state: &str) -> Vec<Transition> {
+    tm![
+        (return_state) ANY => ANY, L, (return_state);
+        (return_state) LEFT_WALL => LEFT_WALL, R, (target_state);
+    ]
+}
+
+fn generate_check_logic(
+    check_state: &str,
+    on_any_state: &str,
+    on_symbol: char,
+    on_symbol_new_symbol: char,
+    on_symbol_direction: Direction,
+    on_symbol_new_state: &str,
+) -> Vec<Transition> {
+    tm![
+        (check_state) ANY => ANY, S, (on_any_state);
+        (check_state) on_symbol => on_symbol_new_symbol, (on_symbol_direction), (on_symbol_new_state);
+    ]
+}
+
+fn generate_setup_transitions(renamed_start_state: &str) -> Vec<Transition> {
+    let write_end_marker_state = "q_write_end_marker";
+    let return_head_state = "q_return_head";
+    let q_carry_0 = "q_carry_0";
+    let q_carry_1 = "q_carry_1";
+
+    tm![
+        (START_STATE) '0' => LEFT_WALL, R, (q_carry_0);
+        (START_STATE) '1' => LEFT_WALL, R, (q_carry_1);
+    ]
+    .into_iter()
+    .chain(generate_carry_logic(q_carry_0, q_carry_1))
+    .chain(tm![
+        (q_carry_0) BLANK => '0', R, (write_end_marker_state);
+        (q_carry_1) BLANK => '1', R, (write_end_marker_state);
+        (write_end_marker_state) BLANK => RIGHT_WALL, L, (return_head_state);
+    ])
+    .chain(generate_return_head_logic(
+        return_head_state,
+        renamed_start_state,
+    ))
+    .chain(tm![
+        (START_STATE) BLANK => LEFT_WALL, R, q_write_end_marker_empty;
+        q_write_end_marker_empty BLANK => RIGHT_WALL, L, (renamed_start_state);
+    ])
+    .collect()
+}
+
+fn generate_check_right_logic(state: &str) -> Vec<Transition> {
+    let check_right_state = format!("check_right_{}", state);
+    let expand_right_state = format!("expand_right_{}", state);
+
+    generate_check_logic(
+        &check_right_state,
+        state,
+        RIGHT_WALL,
+        BLANK,
+        Direction::Right,
+        &expand_right_state,
+    )
+    .into_iter()
+    .chain(tm![(expand_right_state) BLANK => RIGHT_WALL, L, (state)])
+    .collect()
+}
+
+fn generate_shift_sub_logic(state_suffix: &str, shift_start_state: &str) -> Vec<Transition> {
+    let carry_0 = &format!("shift_carry_0_{}", state_suffix);
+    let carry_1 = &format!("shift_carry_1_{}", state_suffix);
+    let write_end = &format!("shift_write_end_{}", state_suffix);
+    let return_s = &format!("shift_return_{}", state_suffix);
+
+    tm![
+        (shift_start_state) '0' => BLANK, R, (carry_0);
+        (shift_start_state) '1' => BLANK, R, (carry_1);
+        (shift_start_state) BLANK => BLANK, S, (state_suffix);
+    ]
+    .into_iter()
+    .chain(generate_carry_logic(carry_0, carry_1))
+    .chain(tm![
+        (carry_0) BLANK => '0', R, (write_end);
+        (carry_1) BLANK => '1', R, (write_end);
+        (carry_0) RIGHT_WALL => '0', R, (write_end);
+        (carry_1) RIGHT_WALL => '1', R, (write_end);
+        (shift_start_state) RIGHT_WALL => BLANK, R, (write_end);
+        (write_end) BLANK => RIGHT_WALL, L, (return_s);
+    ])
+    .chain(generate_return_head_logic(return_s, state_suffix))
+    .collect()
+}
+
+fn generate_check_left_logic(state: &str) -> Vec<Transition> {
+    let check_left_state = format!("check_left_{}", state);
+    let shift_start_state = format!("shift_start_{}", state);
+
+    generate_check_logic(
+        &check_left_state,
+        state,
+        LEFT_WALL,
+        LEFT_WALL,
+        Direction::Right,
+        &shift_start_state,
+    )
+    .into_iter()
+    .chain(generate_shift_sub_logic(state, &shift_start_state))
+    .collect()
+}
+
+/// Source state names interned by a rewriter. The renamed and check-state names of each
+/// state are formatted once, the first time it is seen, rather than once per rule.
+struct RenamedStates {
+    check_prefixes: &'static [&'static str],
+    table: StateTable,
+    /// Indexed by [`StateId`]: the renamed state, then its check state for each prefix.
+    names: Vec<(Rc<str>, Vec<Rc<str>>)>,
+    /// Non-halting target states in order of first appearance, so generated blocks follow
+    /// the source order.
+    targets: Vec<StateId>,
+    targeted: Vec<bool>,
+}
+
+impl RenamedStates {
+    fn new(check_prefixes: &'static [&'static str]) -> Self {
+        RenamedStates {
+            check_prefixes,
+            table: StateTable::new(),
+            names: Vec::new(),
+            targets: Vec::new(),
+            targeted: Vec::new(),
+        }
+    }
+
+    fn intern(&mut self, state: &str) -> StateId {
+        let id = self.table.intern(state);
+        if id.index() == self.names.len() {
+            let renamed = rename_original_state(state, SIM_PREFIX);
+            let checks = self
+                .check_prefixes
+                .iter()
+                .map(|prefix| get_next_state(&renamed, prefix).into())
+                .collect();
+            self.names.push((renamed.into(), checks));
+            self.targeted.push(false);
+        }
+        id
+    }
+
+    fn renamed(&self, id: StateId) -> Rc<str> {
+        Rc::clone(&self.names[id.index()].0)
+    }
+
+    /// Check state of `id` for the `prefix`-th check prefix; a halt state is its own.
+    fn check(&self, id: StateId, prefix: usize) -> Rc<str> {
+        Rc::clone(&self.names[id.index()].1[prefix])
+    }
+
+    fn add_target(&mut self, id: StateId) {
+        if !self.table.is_halt(id) && !self.targeted[id.index()] {
+            self.targeted[id.index()] = true;
+            self.targets.push(id);
+        }
+    }
+
+    /// Renamed states of `t`'s current and next state, and their IDs.
+    fn rename(&mut self, t: &Transition) -> (RewrittenRule, StateId, StateId) {
+        let current = self.intern(&t.current_state);
+        let next = self.intern(&t.new_state);
+        let renamed = RewrittenRule {
+            current_state: self.renamed(current),
+            current_symbol: t.current_symbol,
+            new_symbol: t.new_symbol,
+            direction: t.direction,
+            new_state: self.renamed(next),
+        };
+        (renamed, current, next)
+    }
+
+    /// Renamed names of the target states, consuming the table.
+    fn into_targets(self) -> impl Iterator<Item = Rc<str>> {
+        let names = self.names;
+        self.targets
+            .into_iter()
+            .map(move |id| Rc::clone(&names[id.index()].0))
+    }
+}
+
+/// Infinite-to-Sipser conversion, one rule at a time.
+struct ToSipser {
+    renamed_start_state: String,
+    states: RenamedStates,
+}
+
+impl ToSipser {
+    const CHECK_RIGHT: usize = 0;
+    const CHECK_LEFT: usize = 1;
+
+    fn new(start_state: &str) -> Self {
+        ToSipser {
+            renamed_start_state: rename_original_state(start_state, SIM_PREFIX),
+            states: RenamedStates::new(&["check_right_", "check_left_"]),
+        }
+    }
+}
+
+impl model::RuleRewriter for ToSipser {
+    fn setup(&self) -> Vec<Transition> {
+        generate_setup_transitions(&self.renamed_start_state)
+    }
+
+    fn rewrite(&mut self, transition: &Transition) -> RewrittenRule {
+        let (t, current, next) = self.states.rename(transition);
+        self.states.add_target(next);
+        if self.states.table.is_halt(current) {
+            return t;
+        }
+        let check = match t.direction {
+            Direction::Stay => return t,
+            Direction::Right => ToSipser::CHECK_RIGHT,
+            Direction::Left => ToSipser::CHECK_LEFT,
+        };
+        RewrittenRule {
+            new_state: self.states.check(next, check),
+            ..t
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Box<dyn Iterator<Item = Transition>> {
+        Box::new(self.states.into_targets().flat_map(|state| {
+            generate_check_right_logic(&state)
+                .into_iter()
+                .chain(generate_check_left_logic(&state))
+        }))
+    }
+}
+
+fn generate_wall_setup_transitions(renamed_start_state: &str) -> Vec<Transition> {
+    tm![
+        (START_STATE) ANY => ANY, L, q_write_wall;
+        q_write_wall BLANK => LEFT_WALL, R, (renamed_start_state);
+    ]
+}
+
+/// Sipser-to-Infinite conversion, one rule at a time.
+struct ToInfinite {
+    renamed_start_state: String,
+    states: RenamedStates,
+}
+
+impl ToInfinite {
+    fn new(start_state: &str) -> Self {
+        ToInfinite {
+            renamed_start_state: rename_original_state(start_state, SIM_PREFIX),
+            states: RenamedStates::new(&["check_left_wall_"]),
+        }
+    }
+}
+
+impl model::RuleRewriter for ToInfinite {
+    fn setup(&self) -> Vec<Transition> {
+        generate_wall_setup_transitions(&self.renamed_start_state)
+    }
+
+    fn rewrite(&mut self, transition: &Transition) -> RewrittenRule {
+        let (t, _, next) = self.states.rename(transition);
+        if t.direction != Direction::Left {
+            return t;
+        }
+        self.states.add_target(next);
+        RewrittenRule {
+            new_state: self.states.check(next, 0),
+            ..t
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Box<dyn Iterator<Item = Transition>> {
+        Box::new(self.states.into_targets().flat_map(|state| {
+            generate_check_logic(
+                &format!("check_left_wall_{}", state),
+                &state,
+                LEFT_WALL,
+                LEFT_WALL,
+                Direction::Stay,
+                HALT_PREFIX,
+            )
+        }))
+    }
+}
+
+/// Whitespace other than spaces and tabs, which editors tend to hide.
+fn unusual_whitespace(line_number: usize, line: &str) -> Option<diagnostics::Diagnostic> {
+    let (i, c) = line
+        .char_indices()
+        .find(|&(_, c)| (c.is_whitespace() && c != ' ' && c != '\t') || c == '\u{feff}')?;
+    Some(diagnostics::Diagnostic::warning(
+        line_number,
+        line,
+        i..i + c.len_utf8(),
+        format!("Unusual whitespace character U+{:04X}", c as u32),
+    ))
+}
+
+/// The model of `registry` named by the header line of a text machine file.
+fn parse_header(
+    registry: &model::Registry,
+    line: Option<&str>,
+) -> Result<MachineType, ConversionError> {
+    let line = line.ok_or_else(|| ConversionError::InvalidHeader("File is empty".to_string()))?;
+    registry
+        .from_header(line)
+        .ok_or_else(|| ConversionError::InvalidHeader(line.to_string()))
+}
+
+/// The text parser fed one line at a time, so that files can also be read as a stream.
+/// It keeps the header, the directives and the diagnostics, but not the rules.
+struct TextParser {
+    mode: ParseMode,
+    in_header: bool,
+    pending_comments: Vec<String>,
+    /// Comments that ended at a blank line, kept for the next rule.
+    detached_comments: Vec<String>,
+    comments: Vec<String>,
+    start_state: Option<String>,
+    initial_input: Option<String>,
+    errors: Vec<diagnostics::Diagnostic>,
+    warnings: Vec<diagnostics::Diagnostic>,
+}
+
+impl TextParser {
+    fn new(mode: ParseMode) -> Self {
+        TextParser {
+            mode,
+            in_header: true,
+            pending_comments: Vec::new(),
+            detached_comments: Vec::new(),
+            comments: Vec::new(),
+            start_state: None,
+            initial_input: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Reads the 1-based line `number`, returning the rule on it, if it holds a valid one.
+    fn line(&mut self, number: usize, line: &str) -> Option<Rule> {
+        let trimmed = line.trim();
+        if let Some(start) = trimmed.strip_prefix(START_STATE_DIRECTIVE) {
+            self.start_state = Some(start.trim().to_string());
+        } else if let Some(input) = trimmed.strip_prefix(INITIAL_INPUT_DIRECTIVE) {
+            self.initial_input = Some(input.trim().to_string());
+        } else if let Some(comment) = trimmed.strip_prefix(';') {
+            if self.in_header {
+                self.comments.push(comment.to_string());
+            } else {
+                self.pending_comments.push(comment.to_string());
+            }
+        } else if trimmed.is_empty() {
+            self.in_header &= self.comments.is_empty();
+            self.detached_comments.append(&mut self.pending_comments);
+        } else {
+            self.in_header = false;
+            let leading_comments = std::mem::take(&mut self.pending_comments);
+            let detached_comments = std::mem::take(&mut self.detached_comments);
+            self.warnings.extend(unusual_whitespace(number, line));
+            match parse_rule(line, number, self.mode) {
+                Ok(rule) => {
+                    return Some(Rule {
+                        leading_comments,
+                        detached_comments,
+                        ..rule
+                    });
+                }
+                Err(LineError { error, span }) => self.errors.push(diagnostics::Diagnostic::new(
+                    number,
+                    line,
+                    span,
+                    error.to_string(),
+                )),
+            }
+        }
+        None
+    }
+
+    /// Comments left over after the last rule, once every line has been read.
+    fn trailing_comments(&mut self) -> Vec<String> {
+        let mut trailing = std::mem::take(&mut self.detached_comments);
+        trailing.append(&mut self.pending_comments);
+        trailing
+    }
+
+    /// Fails with every error and warning, sorted by line, if any line failed to parse.
+    fn check(&mut self) -> Result<(), ConversionError> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        let mut diagnostics = std::mem::take(&mut self.errors);
+        diagnostics.append(&mut self.warnings);
+        diagnostics.sort_by_key(|d| d.line);
+        Err(ConversionError::ParseError(diagnostics))
+    }
+}
+
+/// Parses a machine in the text format, reporting every bad line at once. The header must
+/// name a model of the [standard](model::Registry::standard) registry.
+pub fn parse_machine(content: &str, mode: ParseMode) -> Result<Machine, ConversionError> {
+    parse_machine_with(&model::Registry::standard(), content, mode)
+}
+
+/// [`parse_machine`] accepting the headers of the models in `registry`.
+pub fn parse_machine_with(
+    registry: &model::Registry,
+    content: &str,
+    mode: ParseMode,
+) -> Result<Machine, ConversionError> {
+    let mut lines = content.lines().enumerate();
+    let model = parse_header(registry, lines.next().map(|(_, line)| line))?;
+
+    let mut parser = TextParser::new(mode);
+    let rules = lines
+        .filter_map(|(i, line)| parser.line(i + 1, line))
+        .collect();
+    parser.check()?;
+
+    let mut machine = Machine::new(model, rules);
+    machine.trailing_comments = parser.trailing_comments();
+    machine.comments = parser.comments;
+    machine.initial_input = parser.initial_input;
+    machine.warnings = parser.warnings;
+    if let Some(start) = parser.start_state {
+        machine.start_state = start;
+    }
+    Ok(machine)
+}
+
+/// Parses `content` in the format named by `extension` (`json`, `bb`, `enc` or text),
+/// detecting it from the content when there is no extension.
+pub fn parse_input(
+    content: &str,
+    extension: Option<&str>,
+    mode: ParseMode,
+) -> Result<Machine, ConversionError> {
+    parse_input_with(&model::Registry::standard(), content, extension, mode)
+}
+
+/// [`parse_input`] resolving model headers, JSON keys and binary model numbers in
+/// `registry`.
+pub fn parse_input_with(
+    registry: &model::Registry,
+    content: &str,
+    extension: Option<&str>,
+    mode: ParseMode,
+) -> Result<Machine, ConversionError> {
+    let extension = extension.unwrap_or_else(|| {
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('{') {
+            "json"
+        } else if trimmed.starts_with(';') {
+            "in"
+        } else if !trimmed.is_empty()
+            && trimmed
+                .chars()
+                .all(|c| matches!(c, '0' | '1') || c.is_whitespace())
+        {
+            "enc"
+        } else {
+            "bb"
+        }
+    });
+    match extension {
+        "json" => Ok(json::machine_from_json_with(
+            registry,
+            &json::parse(content)?,
+        )?),
+        "bb" => Ok(busy_beaver::parse(content)?),
+        "enc" => Ok(encoding::decode_with(registry, content)?),
+        _ => parse_machine_with(registry, content, mode),
+    }
+}
+
+/// `diagnostics` pointing at the file `path`.
+fn in_file(
+    mut diagnostics: Vec<diagnostics::Diagnostic>,
+    path: &str,
+) -> Vec<diagnostics::Diagnostic> {
+    for diagnostic in &mut diagnostics {
+        diagnostic.file = Some(path.to_string());
+    }
+    diagnostics
+}
+
+/// Reads and parses the machine file at `input_path`, choosing the format by extension.
+pub fn read_machine(input_path: &str, mode: ParseMode) -> Result<Machine, ConversionError> {
+    let content = fs::read_to_string(input_path)?;
+    let in_file = |diagnostics| in_file(diagnostics, input_path);
+    let mut machine = parse_input(
+        &content,
+        Path::new(input_path).extension().and_then(|s| s.to_str()),
+        mode,
+    )
+    .map_err(|e| match e {
+        ConversionError::ParseError(diagnostics) => {
+            ConversionError::ParseError(in_file(diagnostics))
+        }
+        e => e,
+    })?;
+    machine.warnings = in_file(std::mem::take(&mut machine.warnings));
+    Ok(machine)
+}
+
+/// Renders `machine` in `format`.
+pub fn export_machine(machine: &Machine, format: ExportFormat) -> Result<String, ConversionError> {
+    match format {
+        ExportFormat::Text => Ok(machine.to_string()),
+        ExportFormat::Json => Ok(format!("{}\n", json::machine_to_json(machine))),
+        ExportFormat::BusyBeaver => Ok(format!("{}\n", busy_beaver::to_notation(machine)?)),
+        ExportFormat::Binary => Ok(format!("{}\n", encoding::encode(machine)?)),
+        ExportFormat::Mermaid { collapse } => Ok(mermaid::to_mermaid(machine, collapse)),
+        ExportFormat::Markdown => Ok(table::to_markdown(machine)),
+        ExportFormat::Html => Ok(table::to_html(machine)),
+    }
+}
+
+/// Converts `machine` to the default target of its model in the
+/// [standard](model::Registry::standard) registry.
+pub fn convert(machine: &Machine) -> Result<Machine, ConversionError> {
+    model::Registry::standard().convert(machine, None)
+}
+
+fn to_sipser(machine: &Machine) -> Machine {
+    rewrite_machine(
+        machine,
+        MachineType::Sipser,
+        Box::new(ToSipser::new(&machine.start_state)),
+    )
+}
+
+fn to_infinite(machine: &Machine) -> Machine {
+    rewrite_machine(
+        machine,
+        MachineType::Infinite,
+        Box::new(ToInfinite::new(&machine.start_state)),
+    )
+}
+
+/// Runs `rewriter` over the rules of `machine`. Each rewritten rule keeps the line,
+/// breakpoint and comments of its source rule.
+fn rewrite_machine(
+    machine: &Machine,
+    model: MachineType,
+    mut rewriter: Box<dyn model::RuleRewriter>,
+) -> Machine {
+    let setup = rewriter.setup();
+    let rewritten: Vec<Rule> = machine
+        .rules
+        .iter()
+        .map(|source| Rule::annotated_like(rewriter.rewrite(&source.transition).into(), source))
+        .collect();
+    let rules = setup
+        .into_iter()
+        .map(Rule::new)
+        .chain(rewritten)
+        .chain(rewriter.finish().map(Rule::new))
+        .collect();
+
+    let mut converted = Machine::new(model, rules);
+    converted.comments = machine.comments.clone();
+    converted.trailing_comments = machine.trailing_comments.clone();
+    converted.initial_input = machine.initial_input.clone();
+    converted
+}
+
+/// Text rendering of a converted machine, as written to `.out` files.
+pub fn render_conversion(converted: &Machine) -> String {
+    format!("{}{}", conversion_header(converted.model), converted.body())
+}
+
+/// The lines that start a file converted to `model`.
+fn conversion_header(model: MachineType) -> String {
+    format!(
+        "{}\n{START_STATE_DIRECTIVE} {START_STATE}\n",
+        model.model().banner()
+    )
+}
+
+/// Converts `machine` to `target` (the default target of its model when `None`) and
+/// renders the result. A machine already in the target model is rendered unchanged.
+pub fn convert_to(
+    machine: &Machine,
+    target: Option<MachineType>,
+    format: ExportFormat,
+) -> Result<(Machine, String), ConversionError> {
+    convert_with(&model::Registry::standard(), machine, target, format)
+}
+
+/// [`convert_to`] using the models and conversions in `registry`.
+pub fn convert_with(
+    registry: &model::Registry,
+    machine: &Machine,
+    target: Option<MachineType>,
+    format: ExportFormat,
+) -> Result<(Machine, String), ConversionError> {
+    if target == Some(machine.model) {
+        return Ok((machine.clone(), export_machine(machine, format)?));
+    }
+    let converted = registry.convert(machine, target)?;
+    let rendered = match format {
+        ExportFormat::Text => render_conversion(&converted),
+        format => export_machine(&converted, format)?,
+    };
+    Ok((converted, rendered))
+}
+
+/// Sizes before and after a conversion done by [`run_converter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub source_model: MachineType,
+    pub target_model: MachineType,
+    pub source_states: usize,
+    pub source_transitions: usize,
+    pub output_states: usize,
+    pub output_transitions: usize,
+    pub warnings: Vec<diagnostics::Diagnostic>,
+}
+
+/// Converts the machine file at `input_path` and writes the result to `output_path`. Text
+/// files converted to text are [streamed](stream::convert_file) rather than loaded whole.
+pub fn run_converter(
+    input_path: &str,
+    output_path: &str,
+    target: Option<MachineType>,
+    format: ExportFormat,
+    mode: ParseMode,
+) -> Result<ConversionReport, ConversionError> {
+    if format == ExportFormat::Text
+        && let Some(report) = stream::convert_file(
+            &model::Registry::standard(),
+            input_path,
+            output_path,
+            target,
+            mode,
+        )?
+    {
+        return Ok(report);
+    }
+    let machine = read_machine(input_path, mode)?;
+    let (converted, rendered) = convert_to(&machine, target, format)?;
+
+    let mut output_file = fs::File::create(output_path)?;
+    write!(output_file, "{}", rendered)?;
+
+    Ok(ConversionReport {
+        source_model: machine.model,
+        target_model: converted.model,
+        source_states: machine.states().len(),
+        source_transitions: machine.rules.len(),
+        output_states: converted.states().len(),
+        output_transitions: converted.rules.len(),
+        warnings: machine.warnings,
+    })
+}
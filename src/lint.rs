@@ -0,0 +1,161 @@
+//! Static checks on a parsed machine.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use crate::constants::ANY_STATE;
+use crate::model::Registry;
+use crate::{Machine, is_halt_state};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    pub severity: Severity,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}: {}: {}", line, self.severity, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+fn lint(severity: Severity, line: Option<usize>, message: String) -> Lint {
+    Lint {
+        severity,
+        line,
+        message,
+    }
+}
+
+pub fn check(machine: &Machine) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    let mut first_definition: HashMap<(&str, char), Option<usize>> = HashMap::new();
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        let key = (t.current_state.as_str(), t.current_symbol);
+        match first_definition.get(&key) {
+            Some(previous) => lints.push(lint(
+                Severity::Error,
+                rule.line,
+                format!(
+                    "duplicate rule for state '{}' reading '{}'{}; only the first one fires",
+                    t.current_state,
+                    t.current_symbol,
+                    previous.map_or(String::new(), |l| format!(" (first defined on line {l})"))
+                ),
+            )),
+            None => {
+                first_definition.insert(key, rule.line);
+            }
+        }
+    }
+
+    let registry = Registry::standard();
+    let reserved: Vec<char> = registry
+        .conversions_from(machine.model)
+        .flat_map(|c| c.reserved_symbols().iter().copied())
+        .collect();
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        for symbol in [t.current_symbol, t.new_symbol] {
+            if reserved.contains(&symbol) {
+                lints.push(lint(
+                    Severity::Warning,
+                    rule.line,
+                    format!("symbol '{symbol}' is reserved as a wall marker by the conversion"),
+                ));
+                break;
+            }
+        }
+        if is_halt_state(&t.current_state) {
+            lints.push(lint(
+                Severity::Warning,
+                rule.line,
+                format!("rule for halt state '{}' can never fire", t.current_state),
+            ));
+        }
+    }
+
+    let mut outgoing: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut first_use: HashMap<&str, Option<usize>> = HashMap::new();
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        let next = t.next_state();
+        outgoing
+            .entry(t.current_state.as_str())
+            .or_default()
+            .push(next);
+        first_use.entry(next).or_insert(rule.line);
+    }
+
+    if !outgoing.contains_key(machine.start_state.as_str()) && !outgoing.contains_key(ANY_STATE) {
+        lints.push(lint(
+            Severity::Warning,
+            None,
+            format!("start state '{}' has no rules", machine.start_state),
+        ));
+    }
+
+    let mut reachable: HashSet<&str> = HashSet::from([machine.start_state.as_str()]);
+    let mut queue = VecDeque::from([machine.start_state.as_str()]);
+    while let Some(state) = queue.pop_front() {
+        let wildcard = outgoing.get(ANY_STATE).into_iter().flatten();
+        for &next in outgoing.get(state).into_iter().flatten().chain(wildcard) {
+            if reachable.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    for rule in &machine.rules {
+        let state = rule.transition.current_state.as_str();
+        if state != ANY_STATE && !reachable.contains(state) {
+            lints.push(lint(
+                Severity::Warning,
+                rule.line,
+                format!("state '{state}' is unreachable from the start state"),
+            ));
+            reachable.insert(state);
+        }
+    }
+
+    let mut dead_ends: Vec<(&str, Option<usize>)> = first_use
+        .iter()
+        .filter(|(state, _)| {
+            !is_halt_state(state)
+                && !outgoing.contains_key(*state)
+                && !outgoing.contains_key(ANY_STATE)
+        })
+        .map(|(&state, &line)| (state, line))
+        .collect();
+    dead_ends.sort_by_key(|&(state, line)| (line, state));
+    for (state, line) in dead_ends {
+        lints.push(lint(
+            Severity::Warning,
+            line,
+            format!("state '{state}' has no rules and does not halt; the machine gets stuck there"),
+        ));
+    }
+
+    lints.sort_by_key(|l| l.line);
+    lints
+}
@@ -0,0 +1,183 @@
+//! Mermaid `stateDiagram-v2` export.
+//!
+//! Every state is declared as `state "<name>" as sN` so arbitrary state names are safe to
+//! render. When collapsing, each `sim_` state of a converted machine is drawn as a composite
+//! state holding the helper states generated for it (`check_right_sim_0`, `shift_*_sim_0`, …);
+//! transitions between groups are attached to the composites.
+//!
+//! The `*` state is not drawn: like the transition tables, its rules are drawn on every state
+//! that is not a halt state, for the symbols that state has no rule of its own for.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::constants::{ANY, ANY_STATE, SIM_PREFIX};
+use crate::{Machine, Transition, is_halt_state};
+
+const INDENT: &str = "    ";
+
+fn escape(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| match c {
+            '#' | ';' | ':' | '"' => format!("#{};", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn label(t: &Transition) -> String {
+    let written = if t.new_symbol == t.current_symbol {
+        ANY
+    } else {
+        t.new_symbol
+    };
+    escape(&format!("{}/{},{}", t.current_symbol, written, t.direction))
+}
+
+/// Finds the `sim_` state a generated helper state belongs to, if any.
+fn owner<'a>(state: &'a str, states: &BTreeSet<&str>) -> Option<&'a str> {
+    state
+        .match_indices(SIM_PREFIX)
+        .map(|(i, _)| &state[i..])
+        .find(|candidate| states.contains(candidate))
+}
+
+/// The machine's rules with those of the `*` state replaced by the rules they stand for on
+/// each of `states`, in the simulator's order: a state's own rule for the symbol or its `*`
+/// rule comes first, and halt states take none.
+fn expand_any_state(machine: &Machine, states: &[&str]) -> Vec<Transition> {
+    let mut own: HashSet<(&str, char)> = HashSet::new();
+    let mut shared: Vec<&Transition> = Vec::new();
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        if t.current_state != ANY_STATE {
+            own.insert((t.current_state.as_str(), t.current_symbol));
+        } else if shared.iter().all(|s| s.current_symbol != t.current_symbol) {
+            shared.push(t);
+        }
+    }
+    let mut edges: Vec<Transition> = machine
+        .rules
+        .iter()
+        .map(|rule| &rule.transition)
+        .filter(|t| t.current_state != ANY_STATE)
+        .cloned()
+        .collect();
+    for &state in states.iter().filter(|s| !is_halt_state(s)) {
+        for t in &shared {
+            let symbol = t.current_symbol;
+            if !own.contains(&(state, symbol)) && !own.contains(&(state, ANY)) {
+                edges.push(t.applied(state, symbol));
+            }
+        }
+    }
+    edges
+}
+
+pub fn to_mermaid(machine: &Machine, collapse: bool) -> String {
+    let mut states: Vec<&str> = vec![&machine.start_state];
+    for rule in &machine.rules {
+        for state in [
+            rule.transition.current_state.as_str(),
+            rule.transition.next_state(),
+        ] {
+            if state != ANY_STATE && !states.contains(&state) {
+                states.push(state);
+            }
+        }
+    }
+    let edges = expand_any_state(machine, &states);
+    let ids: HashMap<&str, String> = states
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| (s, format!("s{i}")))
+        .collect();
+
+    let known: BTreeSet<&str> = states.iter().copied().collect();
+    let groups: HashMap<&str, &str> = if collapse {
+        states
+            .iter()
+            .filter_map(|&s| owner(s, &known).map(|o| (s, o)))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let mut members: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (&state, &group) in &groups {
+        members.entry(group).or_default().push(state);
+    }
+    let group_ids: HashMap<&str, String> = members
+        .keys()
+        .enumerate()
+        .map(|(i, &g)| (g, format!("g{i}")))
+        .collect();
+
+    let mut out = String::from("stateDiagram-v2\n");
+    for &state in &states {
+        if !groups.contains_key(state) {
+            out.push_str(&format!(
+                "{INDENT}state \"{}\" as {}\n",
+                escape(state),
+                ids[state]
+            ));
+        }
+    }
+
+    let mut inner_edges: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut outer_edges: Vec<String> = Vec::new();
+    for t in &edges {
+        let (from, to) = (t.current_state.as_str(), t.next_state());
+        match (groups.get(from), groups.get(to)) {
+            (Some(a), Some(b)) if a == b => inner_edges.entry(a).or_default().push(format!(
+                "{} --> {} : {}",
+                ids[from],
+                ids[to],
+                label(t)
+            )),
+            (from_group, to_group) => {
+                let node = |state: &str, group: Option<&&str>| {
+                    group.map_or_else(|| ids[state].clone(), |g| group_ids[g].clone())
+                };
+                outer_edges.push(format!(
+                    "{} --> {} : {}",
+                    node(from, from_group),
+                    node(to, to_group),
+                    label(t)
+                ));
+            }
+        }
+    }
+
+    for (group, mut group_states) in members {
+        group_states.sort_by_key(|s| states.iter().position(|x| x == s));
+        out.push_str(&format!(
+            "{INDENT}state \"{}\" as {} {{\n",
+            escape(group),
+            group_ids[group]
+        ));
+        for state in group_states {
+            out.push_str(&format!(
+                "{INDENT}{INDENT}state \"{}\" as {}\n",
+                escape(state),
+                ids[state]
+            ));
+        }
+        for edge in inner_edges.remove(group).unwrap_or_default() {
+            out.push_str(&format!("{INDENT}{INDENT}{edge}\n"));
+        }
+        out.push_str(&format!("{INDENT}}}\n"));
+    }
+
+    let start = groups.get(machine.start_state.as_str()).map_or_else(
+        || ids[machine.start_state.as_str()].clone(),
+        |g| group_ids[g].clone(),
+    );
+    out.push_str(&format!("{INDENT}[*] --> {start}\n"));
+    for edge in outer_edges {
+        out.push_str(&format!("{INDENT}{edge}\n"));
+    }
+    for &state in states.iter().filter(|s| is_halt_state(s)) {
+        out.push_str(&format!("{INDENT}{} --> [*]\n", ids[state]));
+    }
+    out
+}
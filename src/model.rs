@@ -0,0 +1,298 @@
+//! Tape models and the conversions between them.
+//!
+//! A [`Registry`] holds the known [`TapeModel`]s and [`Conversion`]s and finds the shortest
+//! chain of conversions between two models, so [`convert_to`](crate::convert_to) and
+//! [`run_converter`](crate::run_converter), [`convert`](crate::convert) and
+//! [`verify`](crate::verify::verify) never name a model themselves. A new model only needs a
+//! `TapeModel` impl in a `static`, a [`MachineType`] wrapping it and conversions to and from
+//! at least one registered model, all of which can live outside this crate and be passed
+//! around in a custom registry. Text headers, JSON model keys and binary model numbers are
+//! looked up in the registry given to [`parse_input_with`](crate::parse_input_with) and its
+//! siblings, or in the [standard](Registry::standard) one.
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::constants::{LEFT_WALL, RIGHT_WALL, TO_INFINITE_BANNER, TO_SIPSER_BANNER};
+use crate::{
+    ConversionError, Direction, Machine, MachineType, ToInfinite, ToSipser, Transition,
+    to_infinite, to_sipser,
+};
+
+pub trait TapeModel: Sync {
+    /// Lower-case name accepted on the command line and in JSON files, e.g. `sipser`. Two
+    /// models with the same key are the same [`MachineType`].
+    fn key(&self) -> &'static str;
+    /// First line of a machine file in this model, e.g. `;S`.
+    fn header(&self) -> &'static str;
+    /// Whether a left move on the first cell leaves the head in place.
+    fn bounded_left(&self) -> bool;
+    /// Name shown in messages, e.g. `Sipser`.
+    fn name(&self) -> &'static str;
+    /// First line of a file converted to this model.
+    fn banner(&self) -> &'static str;
+}
+
+pub trait Conversion {
+    fn source(&self) -> MachineType;
+    fn target(&self) -> MachineType;
+    fn convert(&self, machine: &Machine) -> Machine;
+
+    /// Symbols the converted machine uses for its own bookkeeping, which source machines
+    /// should avoid.
+    fn reserved_symbols(&self) -> &'static [char] {
+        &[]
+    }
+
+    /// The conversion as a [`RuleRewriter`] for a machine starting in `start_state`, if it
+    /// can run one rule at a time. Only such conversions can be streamed.
+    fn rewriter(&self, _start_state: &str) -> Option<Box<dyn RuleRewriter>> {
+        None
+    }
+}
+
+/// A conversion that never needs the whole machine: it emits its setup rules, then rewrites
+/// each source rule in order, then emits rules generated from what it saw.
+pub trait RuleRewriter {
+    fn setup(&self) -> Vec<Transition>;
+    fn rewrite(&mut self, transition: &Transition) -> RewrittenRule;
+    fn finish(self: Box<Self>) -> Box<dyn Iterator<Item = Transition>>;
+}
+
+/// A source rule after [`RuleRewriter::rewrite`]. Its states are shared with the rewriter's
+/// own table, so rewriting allocates no names; they are copied only when the rule is turned
+/// into a [`Transition`]. It prints like one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewrittenRule {
+    pub current_state: Rc<str>,
+    pub current_symbol: char,
+    pub new_symbol: char,
+    pub direction: Direction,
+    pub new_state: Rc<str>,
+}
+
+pub struct Infinite;
+
+impl TapeModel for Infinite {
+    fn key(&self) -> &'static str {
+        "infinite"
+    }
+
+    fn header(&self) -> &'static str {
+        ";I"
+    }
+
+    fn bounded_left(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "Infinite"
+    }
+
+    fn banner(&self) -> &'static str {
+        TO_INFINITE_BANNER
+    }
+}
+
+pub struct Sipser;
+
+impl TapeModel for Sipser {
+    fn key(&self) -> &'static str {
+        "sipser"
+    }
+
+    fn header(&self) -> &'static str {
+        ";S"
+    }
+
+    fn bounded_left(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "Sipser"
+    }
+
+    fn banner(&self) -> &'static str {
+        TO_SIPSER_BANNER
+    }
+}
+
+/// Simulates the doubly infinite tape on a left-bounded one by shifting the tape contents
+/// right whenever the head would fall off the left wall.
+pub struct InfiniteToSipser;
+
+impl Conversion for InfiniteToSipser {
+    fn source(&self) -> MachineType {
+        MachineType::Infinite
+    }
+
+    fn target(&self) -> MachineType {
+        MachineType::Sipser
+    }
+
+    fn convert(&self, machine: &Machine) -> Machine {
+        to_sipser(machine)
+    }
+
+    fn reserved_symbols(&self) -> &'static [char] {
+        &[LEFT_WALL, RIGHT_WALL]
+    }
+
+    fn rewriter(&self, start_state: &str) -> Option<Box<dyn RuleRewriter>> {
+        Some(Box::new(ToSipser::new(start_state)))
+    }
+}
+
+/// Simulates the left-bounded tape on a doubly infinite one with a wall the machine halts on.
+pub struct SipserToInfinite;
+
+impl Conversion for SipserToInfinite {
+    fn source(&self) -> MachineType {
+        MachineType::Sipser
+    }
+
+    fn target(&self) -> MachineType {
+        MachineType::Infinite
+    }
+
+    fn convert(&self, machine: &Machine) -> Machine {
+        to_infinite(machine)
+    }
+
+    fn reserved_symbols(&self) -> &'static [char] {
+        &[LEFT_WALL]
+    }
+
+    fn rewriter(&self, start_state: &str) -> Option<Box<dyn RuleRewriter>> {
+        Some(Box::new(ToInfinite::new(start_state)))
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    models: Vec<MachineType>,
+    conversions: Vec<Box<dyn Conversion>>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// The models and conversions built into the crate.
+    pub fn standard() -> Self {
+        let mut registry = Registry::new();
+        registry.register_model(MachineType::Infinite);
+        registry.register_model(MachineType::Sipser);
+        registry.register_conversion(InfiniteToSipser);
+        registry.register_conversion(SipserToInfinite);
+        registry
+    }
+
+    pub fn register_model(&mut self, model: MachineType) {
+        self.models.push(model);
+    }
+
+    /// Adds a conversion. When several convert between the same pair of models, the one
+    /// registered first is used.
+    pub fn register_conversion(&mut self, conversion: impl Conversion + 'static) {
+        self.conversions.push(Box::new(conversion));
+    }
+
+    /// The registered models, in registration order.
+    pub fn models(&self) -> impl Iterator<Item = MachineType> {
+        self.models.iter().copied()
+    }
+
+    /// Conversions whose source is `model`, in registration order.
+    pub fn conversions_from(&self, model: MachineType) -> impl Iterator<Item = &dyn Conversion> {
+        self.conversions
+            .iter()
+            .map(Box::as_ref)
+            .filter(move |c| c.source() == model)
+    }
+
+    /// The model whose [`TapeModel::key`] is `key`.
+    pub fn find(&self, key: &str) -> Option<MachineType> {
+        self.models().find(|m| m.key() == key)
+    }
+
+    /// The model whose header or banner is `line`. Converter output starts with a banner
+    /// naming the model it was converted to.
+    pub fn from_header(&self, line: &str) -> Option<MachineType> {
+        self.models()
+            .find(|m| m.header() == line || m.model().banner() == line)
+    }
+
+    /// Target of the first conversion registered for `model`, used when no target is given.
+    pub fn default_target(&self, model: MachineType) -> Option<MachineType> {
+        self.conversions
+            .iter()
+            .find(|c| c.source() == model)
+            .map(|c| c.target())
+    }
+
+    /// Shortest chain of conversions from `from` to `to`, empty when they are the same.
+    pub fn path(&self, from: MachineType, to: MachineType) -> Option<Vec<&dyn Conversion>> {
+        // Breadth-first over models, remembering the conversion that first reached each one.
+        let mut reached: Vec<(MachineType, Option<usize>)> = vec![(from, None)];
+        let mut queue = VecDeque::from([from]);
+        while let Some(model) = queue.pop_front() {
+            if model == to {
+                break;
+            }
+            for (i, conversion) in self.conversions.iter().enumerate() {
+                if conversion.source() == model
+                    && !reached.iter().any(|(m, _)| *m == conversion.target())
+                {
+                    reached.push((conversion.target(), Some(i)));
+                    queue.push_back(conversion.target());
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut model = to;
+        loop {
+            let (_, via) = reached.iter().find(|(m, _)| *m == model)?;
+            match via {
+                Some(i) => {
+                    let conversion = self.conversions[*i].as_ref();
+                    path.push(conversion);
+                    model = conversion.source();
+                }
+                None => break,
+            }
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Converts `machine` to `target`, or to its [default target](Self::default_target).
+    pub fn convert(
+        &self,
+        machine: &Machine,
+        target: Option<MachineType>,
+    ) -> Result<Machine, ConversionError> {
+        let target = target
+            .or_else(|| self.default_target(machine.model))
+            .ok_or(ConversionError::NoConversion {
+                from: machine.model,
+                to: None,
+            })?;
+        let path = self
+            .path(machine.model, target)
+            .ok_or(ConversionError::NoConversion {
+                from: machine.model,
+                to: Some(target),
+            })?;
+        let mut converted = machine.clone();
+        for conversion in path {
+            converted = conversion.convert(&converted);
+        }
+        Ok(converted)
+    }
+}
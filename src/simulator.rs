@@ -0,0 +1,228 @@
+//! Step-by-step simulator following the Morphett conventions used by the text format.
+//!
+//! Rules are matched on `(state, symbol)`, falling back to `(state, *)`, `(*, symbol)` and
+//! `(*, *)`. A `*` written symbol or next state leaves it unchanged. The machine halts on
+//! entering a state whose name starts with `halt`, and gets stuck when no rule matches.
+//! The simulator is generic over the [`Tape`] backend, which decides where moves take the
+//! head: on the Sipser model's tapes a left move on the first cell leaves it in place.
+//!
+//! States are interned into a [`StateTable`] when the simulator is created, so a step only
+//! copies a [`StateId`]; [`Simulator::state`] turns the current one back into its name.
+//!
+//! A run can be saved with [`Simulator::snapshot`] and carried on later, possibly on another
+//! tape backend, with [`Simulator::restore`].
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::Machine;
+use crate::constants::{ANY, ANY_STATE, BLANK};
+use crate::intern::{StateId, StateTable};
+use crate::snapshot::{Snapshot, SnapshotError, machine_hash};
+use crate::tape::{AnyTape, Tape};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Halted(String),
+    Stuck { state: String, symbol: char },
+    StepLimit,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Halted(state) => write!(f, "halted in state {state}"),
+            Outcome::Stuck { state, symbol } => {
+                write!(f, "stuck in state {state} reading '{symbol}'")
+            }
+            Outcome::StepLimit => write!(f, "step limit reached"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Step {
+    Fired(usize),
+    Done(Outcome),
+}
+
+/// State, head, tape and step count of a run. `state` is an ID in the simulator's
+/// [`StateTable`], which is the same for every simulator of a machine; a [`Snapshot`] stores
+/// the state by name instead, and is what to serialize to save a run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Configuration<T: Tape = AnyTape> {
+    pub state: StateId,
+    pub head: i64,
+    pub tape: T,
+    pub steps: u64,
+}
+
+impl<T: Tape> Configuration<T> {
+    /// Tape cells `from..=to`, with the cell under the head in brackets.
+    pub fn render(&self, from: i64, to: i64) -> String {
+        (from..=to)
+            .map(|p| {
+                let symbol = self.tape.read(p);
+                if p == self.head {
+                    format!("[{symbol}]")
+                } else {
+                    symbol.to_string()
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct Simulator<'m, T: Tape = AnyTape> {
+    machine: &'m Machine,
+    states: StateTable,
+    rules: HashMap<(StateId, char), usize>,
+    /// Next state of each rule, `None` when it is `*`.
+    next_states: Vec<Option<StateId>>,
+    /// ID of the `*` state, when some rule matches any state.
+    wildcard: Option<StateId>,
+    pub config: Configuration<T>,
+}
+
+impl<'m> Simulator<'m> {
+    /// A simulator on the tape backend that matches the machine's model.
+    pub fn new(machine: &'m Machine, input: &str) -> Self {
+        Simulator::with_tape(machine, AnyTape::for_model(machine.model, input))
+    }
+}
+
+impl<'m, T: Tape> Simulator<'m, T> {
+    pub fn with_tape(machine: &'m Machine, tape: T) -> Self {
+        let mut states = StateTable::for_machine(machine);
+        let mut rules = HashMap::new();
+        let mut next_states = Vec::with_capacity(machine.rules.len());
+        for (i, rule) in machine.rules.iter().enumerate() {
+            let t = &rule.transition;
+            rules
+                .entry((states.intern(&t.current_state), t.current_symbol))
+                .or_insert(i);
+            next_states.push((t.new_state != ANY_STATE).then(|| states.intern(&t.new_state)));
+        }
+        let wildcard = states.get(ANY_STATE);
+        let start = states.intern(&machine.start_state);
+        Simulator {
+            machine,
+            states,
+            rules,
+            next_states,
+            wildcard,
+            config: Configuration {
+                state: start,
+                head: 0,
+                tape,
+                steps: 0,
+            },
+        }
+    }
+
+    pub fn states(&self) -> &StateTable {
+        &self.states
+    }
+
+    /// Name of the current state.
+    pub fn state(&self) -> &str {
+        self.states.name(self.config.state)
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.states.is_halt(self.config.state)
+    }
+
+    /// Index into `machine.rules` of the rule that would fire next, if any.
+    pub fn next_rule(&self) -> Option<usize> {
+        let state = self.config.state;
+        let symbol = self.config.tape.read(self.config.head);
+        let wildcard = self
+            .wildcard
+            .into_iter()
+            .flat_map(|any| [(any, symbol), (any, ANY)]);
+        [(state, symbol), (state, ANY)]
+            .into_iter()
+            .chain(wildcard)
+            .find_map(|key| self.rules.get(&key).copied())
+    }
+
+    pub fn step(&mut self) -> Step {
+        if self.is_halted() {
+            return Step::Done(Outcome::Halted(self.state().to_string()));
+        }
+        let Some(index) = self.next_rule() else {
+            return Step::Done(Outcome::Stuck {
+                state: self.state().to_string(),
+                symbol: self.config.tape.read(self.config.head),
+            });
+        };
+        let t = &self.machine.rules[index].transition;
+        if t.new_symbol != ANY {
+            self.config.tape.write(self.config.head, t.new_symbol);
+        }
+        self.config.head = self.config.tape.step(self.config.head, t.direction);
+        if let Some(next) = self.next_states[index] {
+            self.config.state = next;
+        }
+        self.config.steps += 1;
+        Step::Fired(index)
+    }
+
+    pub fn run(&mut self, max_steps: u64) -> Outcome {
+        loop {
+            if self.config.steps >= max_steps && !self.is_halted() {
+                return Outcome::StepLimit;
+            }
+            if let Step::Done(outcome) = self.step() {
+                return outcome;
+            }
+        }
+    }
+
+    /// The current configuration, to be resumed later with [`restore`](Self::restore).
+    pub fn snapshot(&self) -> Snapshot {
+        let (left, right) = self.config.tape.bounds();
+        Snapshot {
+            machine: machine_hash(self.machine),
+            state: self.state().to_string(),
+            head: self.config.head,
+            steps: self.config.steps,
+            left,
+            tape: (left..=right).map(|p| self.config.tape.read(p)).collect(),
+        }
+    }
+
+    /// Carries on from `snapshot`, which must have been taken from the same machine. Cells
+    /// the snapshot does not cover are blanked, so the tape ends up holding only its contents.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        snapshot.check(self.machine)?;
+        let state = self
+            .states
+            .get(&snapshot.state)
+            .ok_or_else(|| SnapshotError::UnknownState(snapshot.state.clone()))?;
+        if let Some(edge) = self.config.tape.left_edge()
+            && (snapshot.left < edge || snapshot.head < edge)
+        {
+            return Err(SnapshotError::Invalid(
+                "the tape extends left of the first cell".to_string(),
+            ));
+        }
+        let (left, right) = self.config.tape.bounds();
+        let covered = snapshot.left..snapshot.left + snapshot.tape.chars().count() as i64;
+        for position in left..=right {
+            if !covered.contains(&position) && self.config.tape.read(position) != BLANK {
+                self.config.tape.write(position, BLANK);
+            }
+        }
+        for (position, symbol) in snapshot.cells() {
+            self.config.tape.write(position, symbol);
+        }
+        self.config.state = state;
+        self.config.head = snapshot.head;
+        self.config.steps = snapshot.steps;
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+//! Saved simulation configurations, for resuming long runs.
+//!
+//! A [`Snapshot`] holds everything a simulator needs to carry on where it stopped: the
+//! current state, the head, the tape and the step count, plus a hash of the machine so it is
+//! never resumed against a machine that has changed since. It is stored as a JSON object:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "machine": "9c1f0e4b7a2d5e83",
+//!   "state": "B",
+//!   "head": -2,
+//!   "steps": "4000000",
+//!   "left": -3,
+//!   "tape": "1_11"
+//! }
+//! ```
+//!
+//! - `machine` is the [`machine_hash`] in hexadecimal.
+//! - `steps` is a decimal string, since JSON numbers lose precision above 2^53.
+//! - `tape` holds the cells from position `left` on, and always covers position 0, the first
+//!   input cell. Positions outside it are blank.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use crate::Machine;
+use crate::json::{self, JsonError, Value};
+
+pub const SNAPSHOT_VERSION: u64 = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub machine: u64,
+    pub state: String,
+    pub head: i64,
+    pub steps: u64,
+    /// Position of the first cell in `tape`, at most 0.
+    pub left: i64,
+    pub tape: String,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Json(JsonError),
+    Invalid(String),
+    /// The snapshot was taken from a different machine.
+    MachineChanged,
+    UnknownState(String),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "{e}"),
+            SnapshotError::Json(e) => write!(f, "{e}"),
+            SnapshotError::Invalid(message) => write!(f, "Invalid snapshot: {message}"),
+            SnapshotError::MachineChanged => write!(
+                f,
+                "The machine has changed since the snapshot was taken, refusing to resume"
+            ),
+            SnapshotError::UnknownState(state) => {
+                write!(f, "Snapshot state '{state}' is not in the machine")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            SnapshotError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<JsonError> for SnapshotError {
+    fn from(e: JsonError) -> Self {
+        SnapshotError::Json(e)
+    }
+}
+
+/// 64-bit FNV-1a hash of what decides how the machine runs: its model, start state and
+/// rules, in order. Comments, breakpoints and directives can change without invalidating a
+/// snapshot. The hash is stable across runs and Rust versions, unlike
+/// [`std::hash::DefaultHasher`], so it can be stored in files.
+pub fn machine_hash(machine: &Machine) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let rules = machine.rules.iter().map(|r| r.transition.to_string());
+    [machine.model.key().to_string(), machine.start_state.clone()]
+        .into_iter()
+        .chain(rules)
+        .flat_map(|line| line.into_bytes().into_iter().chain([b'\n']))
+        .fold(OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
+fn invalid(message: impl Into<String>) -> SnapshotError {
+    SnapshotError::Invalid(message.into())
+}
+
+impl Snapshot {
+    /// Fails unless the snapshot was taken from `machine` and its tape covers the first
+    /// input cell.
+    pub fn check(&self, machine: &Machine) -> Result<(), SnapshotError> {
+        if self.machine != machine_hash(machine) {
+            return Err(SnapshotError::MachineChanged);
+        }
+        self.check_tape()
+    }
+
+    fn check_tape(&self) -> Result<(), SnapshotError> {
+        if self.left > 0 || self.left + (self.tape.chars().count() as i64) <= 0 {
+            return Err(invalid("the tape must cover the first input cell"));
+        }
+        Ok(())
+    }
+
+    /// Cells of the tape paired with their positions.
+    pub fn cells(&self) -> impl Iterator<Item = (i64, char)> + '_ {
+        (self.left..).zip(self.tape.chars())
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("version".into(), Value::Number(SNAPSHOT_VERSION as f64)),
+            (
+                "machine".into(),
+                Value::String(format!("{:016x}", self.machine)),
+            ),
+            ("state".into(), Value::String(self.state.clone())),
+            ("head".into(), Value::Number(self.head as f64)),
+            ("steps".into(), Value::String(self.steps.to_string())),
+            ("left".into(), Value::Number(self.left as f64)),
+            ("tape".into(), Value::String(self.tape.clone())),
+        ])
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self, SnapshotError> {
+        if value.get("version").and_then(Value::as_u64) != Some(SNAPSHOT_VERSION) {
+            return Err(invalid(format!(
+                "unsupported version, expected {SNAPSHOT_VERSION}"
+            )));
+        }
+        let field = |key: &str| {
+            value
+                .get(key)
+                .ok_or_else(|| invalid(format!("missing field \"{key}\"")))
+        };
+        let str_field = |key: &str| {
+            field(key)?
+                .as_str()
+                .ok_or_else(|| invalid(format!("field \"{key}\" must be a string")))
+        };
+        let int_field = |key: &str| {
+            field(key)?
+                .as_i64()
+                .ok_or_else(|| invalid(format!("field \"{key}\" must be an integer")))
+        };
+
+        let machine = u64::from_str_radix(str_field("machine")?, 16)
+            .map_err(|_| invalid("field \"machine\" must be a hexadecimal hash"))?;
+        let steps = str_field("steps")?
+            .parse::<u64>()
+            .map_err(|_| invalid("field \"steps\" must be a non-negative integer string"))?;
+        let snapshot = Snapshot {
+            machine,
+            state: str_field("state")?.to_string(),
+            head: int_field("head")?,
+            steps,
+            left: int_field("left")?,
+            tape: str_field("tape")?.to_string(),
+        };
+        snapshot.check_tape()?;
+        Ok(snapshot)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        let text = fs::read_to_string(path)?;
+        Snapshot::from_json(&json::parse(&text)?)
+    }
+
+    /// Writes the snapshot to `path`. It goes to a temporary file first and is then renamed
+    /// over `path`, so an interrupted save leaves the previous snapshot intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, format!("{}\n", self.to_json()))?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! Size report for a conversion, broken down by the generator that produced each rule.
+//!
+//! Generated states are recognised by the prefixes the generators give them; everything
+//! else in the converted machine is a rewritten source rule (`sim_*` and halt states).
+
+use std::collections::BTreeSet;
+
+use crate::Machine;
+use crate::constants::START_STATE;
+use crate::json::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Generator {
+    Source,
+    Setup,
+    RightChecks,
+    LeftChecks,
+    ShiftRoutines,
+    WallChecks,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 6] = [
+        Generator::Source,
+        Generator::Setup,
+        Generator::RightChecks,
+        Generator::LeftChecks,
+        Generator::ShiftRoutines,
+        Generator::WallChecks,
+    ];
+
+    fn classify(state: &str) -> Self {
+        if state == START_STATE || state.starts_with("q_") {
+            Generator::Setup
+        } else if state.starts_with("check_left_wall_") {
+            Generator::WallChecks
+        } else if state.starts_with("check_right_") || state.starts_with("expand_right_") {
+            Generator::RightChecks
+        } else if state.starts_with("check_left_") {
+            Generator::LeftChecks
+        } else if state.starts_with("shift_") {
+            Generator::ShiftRoutines
+        } else {
+            Generator::Source
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::Source => "source rules",
+            Generator::Setup => "setup",
+            Generator::RightChecks => "right checks",
+            Generator::LeftChecks => "left checks",
+            Generator::ShiftRoutines => "shift routines",
+            Generator::WallChecks => "wall checks",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Generator::Source => "source",
+            Generator::Setup => "setup",
+            Generator::RightChecks => "right_checks",
+            Generator::LeftChecks => "left_checks",
+            Generator::ShiftRoutines => "shift_routines",
+            Generator::WallChecks => "wall_checks",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Size {
+    pub states: usize,
+    pub transitions: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub source: Size,
+    pub alphabet: usize,
+    pub output: Size,
+    pub by_generator: Vec<(Generator, Size)>,
+}
+
+fn size(machine: &Machine) -> Size {
+    Size {
+        states: machine.states().len(),
+        transitions: machine.rules.len(),
+    }
+}
+
+pub fn collect(source: &Machine, converted: &Machine) -> Stats {
+    let states = converted.states();
+    let by_generator = Generator::ALL
+        .iter()
+        .map(|&generator| {
+            let owned: BTreeSet<&str> = states
+                .iter()
+                .copied()
+                .filter(|s| Generator::classify(s) == generator)
+                .collect();
+            let transitions = converted
+                .rules
+                .iter()
+                .filter(|r| Generator::classify(&r.transition.current_state) == generator)
+                .count();
+            let size = Size {
+                states: owned.len(),
+                transitions,
+            };
+            (generator, size)
+        })
+        .collect();
+    Stats {
+        source: size(source),
+        alphabet: source.alphabet.len(),
+        output: size(converted),
+        by_generator,
+    }
+}
+
+fn ratio(output: usize, source: usize) -> String {
+    if source == 0 {
+        "-".to_string()
+    } else {
+        format!("x{:.2}", output as f64 / source as f64)
+    }
+}
+
+pub fn to_text(stats: &Stats) -> String {
+    let mut out = format!(
+        "{:<16} {:>8} {:>12}\n",
+        "generator", "states", "transitions"
+    );
+    for (generator, size) in &stats.by_generator {
+        out.push_str(&format!(
+            "{:<16} {:>8} {:>12}\n",
+            generator.name(),
+            size.states,
+            size.transitions
+        ));
+    }
+    out.push_str(&format!(
+        "{:<16} {:>8} {:>12}\n\n",
+        "total", stats.output.states, stats.output.transitions
+    ));
+    out.push_str(&format!(
+        "source: {} states, {} transitions, {} symbols\n",
+        stats.source.states, stats.source.transitions, stats.alphabet
+    ));
+    out.push_str(&format!(
+        "growth: {} states, {} transitions\n",
+        ratio(stats.output.states, stats.source.states),
+        ratio(stats.output.transitions, stats.source.transitions)
+    ));
+    out
+}
+
+fn size_json(size: Size) -> Value {
+    Value::Object(vec![
+        ("states".to_string(), Value::Number(size.states as f64)),
+        (
+            "transitions".to_string(),
+            Value::Number(size.transitions as f64),
+        ),
+    ])
+}
+
+pub fn to_json(stats: &Stats) -> Value {
+    Value::Object(vec![
+        ("source".to_string(), size_json(stats.source)),
+        ("alphabet".to_string(), Value::Number(stats.alphabet as f64)),
+        ("output".to_string(), size_json(stats.output)),
+        (
+            "generators".to_string(),
+            Value::Object(
+                stats
+                    .by_generator
+                    .iter()
+                    .map(|&(generator, size)| (generator.key().to_string(), size_json(size)))
+                    .collect(),
+            ),
+        ),
+    ])
+}
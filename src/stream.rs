@@ -0,0 +1,192 @@
+//! Conversion of text machine files too large to hold in memory.
+//!
+//! The source is read twice, line by line. The first pass, [`prepare`], checks every line and
+//! collects the directives and header comments, so nothing is written for a source that
+//! fails to parse. The second pass, [`StreamedConversion::write`], writes the setup rules,
+//! then each source rule rewritten as it is read, then the rules generated for the states it
+//! saw. Only state names are kept in memory, never the rules, and the output matches
+//! [`convert_to`](crate::convert_to) byte for byte.
+//!
+//! Both passes work on any [`BufRead`] and the output on any [`Write`]; a source that can only
+//! be read once, such as standard input, has to be copied somewhere first.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::constants::START_STATE;
+use crate::diagnostics::Diagnostic;
+use crate::intern::StateTable;
+use crate::model::{Registry, RuleRewriter};
+use crate::{
+    Annotated, ConversionError, ConversionReport, MachineType, ParseMode, TextParser, Transition,
+    conversion_header, epilogue, in_file, parse_header, preamble,
+};
+
+/// Adds the states of `t` to `states`, resolving a `*` next state as
+/// [`Machine::states`](crate::Machine::states) does.
+fn add_states(states: &mut StateTable, t: &Transition) {
+    states.intern(&t.current_state);
+    states.intern(t.next_state());
+}
+
+/// A conversion that can run rule by rule, set up by [`prepare`] from a first pass over the
+/// source. [`write`](Self::write) then makes the second pass.
+pub struct StreamedConversion {
+    model: MachineType,
+    target: MachineType,
+    mode: ParseMode,
+    header: TextParser,
+    rewriter: Box<dyn RuleRewriter>,
+    warnings: Vec<Diagnostic>,
+}
+
+/// Diagnostics of the source named `name`, or of an unnamed source such as standard input.
+fn located(diagnostics: Vec<Diagnostic>, name: Option<&str>) -> Vec<Diagnostic> {
+    match name {
+        Some(name) => in_file(diagnostics, name),
+        None => diagnostics,
+    }
+}
+
+/// Reads `input`, a text machine named `name` (`None` for standard input), and checks every
+/// line. Returns `None` when the source is in another format or no single conversion to
+/// `target` in `registry` can run rule by rule; the caller should then convert in memory.
+pub fn prepare<R: BufRead>(
+    registry: &Registry,
+    input: R,
+    name: Option<&str>,
+    target: Option<MachineType>,
+    mode: ParseMode,
+) -> Result<Option<StreamedConversion>, ConversionError> {
+    let extension = name.and_then(|name| Path::new(name).extension()?.to_str());
+    if matches!(extension, Some("json" | "bb" | "enc")) {
+        return Ok(None);
+    }
+    let mut lines = input.lines();
+    let Ok(model) = parse_header(registry, lines.next().transpose()?.as_deref()) else {
+        return Ok(None);
+    };
+    let Some(target) = target.or_else(|| registry.default_target(model)) else {
+        return Ok(None);
+    };
+    let conversion = match registry.path(model, target).as_deref() {
+        Some(&[conversion]) => conversion,
+        _ => return Ok(None),
+    };
+
+    let mut header = TextParser::new(mode);
+    for (i, line) in lines.enumerate() {
+        header.line(i + 2, &line?);
+    }
+    header.check().map_err(|e| match e {
+        ConversionError::ParseError(diagnostics) => {
+            ConversionError::ParseError(located(diagnostics, name))
+        }
+        e => e,
+    })?;
+    let start_state = header.start_state.as_deref().unwrap_or(START_STATE);
+    let Some(rewriter) = conversion.rewriter(start_state) else {
+        return Ok(None);
+    };
+    let warnings = located(std::mem::take(&mut header.warnings), name);
+    Ok(Some(StreamedConversion {
+        model,
+        target,
+        mode,
+        header,
+        rewriter,
+        warnings,
+    }))
+}
+
+impl StreamedConversion {
+    /// Reads the source again from `input` and writes the converted machine to `output`.
+    /// `input` must hold the same text that was given to [`prepare`].
+    pub fn write<R: BufRead, W: Write>(
+        self,
+        input: R,
+        mut output: W,
+    ) -> Result<ConversionReport, ConversionError> {
+        let StreamedConversion {
+            model,
+            target,
+            mode,
+            header,
+            mut rewriter,
+            warnings,
+        } = self;
+        let start_state = header.start_state.as_deref().unwrap_or(START_STATE);
+        let mut source_states = StateTable::new();
+        let mut output_states = StateTable::new();
+        source_states.intern(start_state);
+        output_states.intern(START_STATE);
+        let mut source_transitions = 0;
+        let mut output_transitions = 0;
+
+        write!(output, "{}", conversion_header(target))?;
+        write!(
+            output,
+            "{}",
+            preamble(
+                START_STATE,
+                header.initial_input.as_deref(),
+                &header.comments
+            )
+        )?;
+        // Takes the rule's current and next state along with the rule to print.
+        let mut write_rule = |current: &str, next: &str, rule: &dyn Display, output: &mut W| {
+            output_states.intern(current);
+            output_states.intern(next);
+            output_transitions += 1;
+            writeln!(output, "{rule}")
+        };
+        for t in rewriter.setup() {
+            write_rule(&t.current_state, t.next_state(), &t, &mut output)?;
+        }
+        let mut parser = TextParser::new(mode);
+        for (i, line) in input.lines().enumerate().skip(1) {
+            if let Some(source) = parser.line(i + 1, &line?) {
+                add_states(&mut source_states, &source.transition);
+                source_transitions += 1;
+                // Printed straight from the rewriter's shared names, never copied.
+                let t = rewriter.rewrite(&source.transition);
+                let rule = Annotated(&source, &t);
+                write_rule(&t.current_state, &t.new_state, &rule, &mut output)?;
+            }
+        }
+        for t in rewriter.finish() {
+            write_rule(&t.current_state, t.next_state(), &t, &mut output)?;
+        }
+        write!(output, "{}", epilogue(&parser.trailing_comments()))?;
+        output.flush()?;
+
+        Ok(ConversionReport {
+            source_model: model,
+            target_model: target,
+            source_states: source_states.len(),
+            source_transitions,
+            output_states: output_states.len(),
+            output_transitions,
+            warnings,
+        })
+    }
+}
+
+/// Streams the conversion of the text machine file at `input_path` to `target` into
+/// `output_path`. Returns `None`, having written nothing, when [`prepare`] does.
+pub fn convert_file(
+    registry: &Registry,
+    input_path: &str,
+    output_path: &str,
+    target: Option<MachineType>,
+    mode: ParseMode,
+) -> Result<Option<ConversionReport>, ConversionError> {
+    let open = || File::open(input_path).map(BufReader::new);
+    let Some(conversion) = prepare(registry, open()?, Some(input_path), target, mode)? else {
+        return Ok(None);
+    };
+    let output = BufWriter::new(File::create(output_path)?);
+    conversion.write(open()?, output).map(Some)
+}
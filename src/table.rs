@@ -0,0 +1,175 @@
+//! State × symbol transition tables in Markdown and HTML.
+//!
+//! Each cell shows `write,dir,next` with `*` resolved against the row and column. A cell is
+//! either defined by a rule for that exact state and symbol, derived from a `*` wildcard
+//! rule, or undefined. Wildcards apply in the simulator's order: the state's `*` rule, then
+//! the `*` state's rule for the symbol, then its `*` rule; the `*` state does not get a row of
+//! its own. A run stops as soon as it enters a halt state, so their rows are all undefined.
+
+use std::collections::HashMap;
+
+use crate::constants::{ANY, ANY_STATE, BLANK, LEFT_WALL, RIGHT_WALL};
+use crate::{Machine, Transition, is_halt_state};
+
+const UNDEFINED_MARK: &str = "—";
+const WILDCARD_MARK: &str = "†";
+
+enum Cell {
+    Explicit(String),
+    Wildcard(String),
+    Undefined,
+}
+
+struct Grid<'m> {
+    states: Vec<&'m str>,
+    symbols: Vec<char>,
+    cells: Vec<Vec<Cell>>,
+}
+
+/// The cell for `t` firing in `state` reading `symbol`.
+fn cell_text(t: &Transition, state: &str, symbol: char) -> String {
+    let t = t.applied(state, symbol);
+    format!("{},{},{}", t.written_symbol(), t.direction, t.next_state())
+}
+
+fn build_grid(machine: &Machine) -> Grid<'_> {
+    let mut states: Vec<&str> = vec![&machine.start_state];
+    for rule in &machine.rules {
+        for state in [
+            rule.transition.current_state.as_str(),
+            rule.transition.next_state(),
+        ] {
+            if state != ANY_STATE && !states.contains(&state) {
+                states.push(state);
+            }
+        }
+    }
+
+    let specials = [BLANK, LEFT_WALL, RIGHT_WALL];
+    let mut symbols: Vec<char> = machine
+        .alphabet
+        .iter()
+        .copied()
+        .filter(|c| *c != ANY && !specials.contains(c))
+        .collect();
+    symbols.extend(specials);
+
+    let mut rules: HashMap<(&str, char), &Transition> = HashMap::new();
+    for rule in &machine.rules {
+        let t = &rule.transition;
+        rules
+            .entry((t.current_state.as_str(), t.current_symbol))
+            .or_insert(t);
+    }
+
+    let cells = states
+        .iter()
+        .map(|&state| {
+            symbols
+                .iter()
+                .map(|&symbol| {
+                    let wildcards = [(state, ANY), (ANY_STATE, symbol), (ANY_STATE, ANY)];
+                    if is_halt_state(state) {
+                        Cell::Undefined
+                    } else if let Some(t) = rules.get(&(state, symbol)) {
+                        Cell::Explicit(cell_text(t, state, symbol))
+                    } else if let Some(t) = wildcards.into_iter().find_map(|key| rules.get(&key)) {
+                        Cell::Wildcard(cell_text(t, state, symbol))
+                    } else {
+                        Cell::Undefined
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Grid {
+        states,
+        symbols,
+        cells,
+    }
+}
+
+/// `text` as a Markdown code span inside a table cell: the fence is longer than any run of
+/// backticks in it, and `|` is escaped so that it does not end the cell.
+fn code_span(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let text = text.replace('|', "\\|");
+    if longest > 0 {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+pub fn to_markdown(machine: &Machine) -> String {
+    let grid = build_grid(machine);
+    let mut out = String::from("| state |");
+    for symbol in &grid.symbols {
+        out.push_str(&format!(" {} |", code_span(&symbol.to_string())));
+    }
+    out.push_str("\n|---|");
+    out.push_str(&"---|".repeat(grid.symbols.len()));
+    out.push('\n');
+    for (state, row) in grid.states.iter().zip(&grid.cells) {
+        out.push_str(&format!("| {} |", code_span(state)));
+        for cell in row {
+            match cell {
+                Cell::Explicit(text) => out.push_str(&format!(" {} |", code_span(text))),
+                Cell::Wildcard(text) => {
+                    out.push_str(&format!(" {} {WILDCARD_MARK} |", code_span(text)))
+                }
+                Cell::Undefined => out.push_str(&format!(" {UNDEFINED_MARK} |")),
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "\n{WILDCARD_MARK} derived from a `*` wildcard rule, {UNDEFINED_MARK} undefined\n"
+    ));
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn to_html(machine: &Machine) -> String {
+    let grid = build_grid(machine);
+    let mut out =
+        String::from("<table class=\"transition-table\">\n  <thead>\n    <tr><th>state</th>");
+    for symbol in &grid.symbols {
+        out.push_str(&format!(
+            "<th><code>{}</code></th>",
+            escape_html(&symbol.to_string())
+        ));
+    }
+    out.push_str("</tr>\n  </thead>\n  <tbody>\n");
+    for (state, row) in grid.states.iter().zip(&grid.cells) {
+        out.push_str(&format!(
+            "    <tr><th><code>{}</code></th>",
+            escape_html(state)
+        ));
+        for cell in row {
+            match cell {
+                Cell::Explicit(text) => {
+                    out.push_str(&format!("<td><code>{}</code></td>", escape_html(text)))
+                }
+                Cell::Wildcard(text) => out.push_str(&format!(
+                    "<td class=\"wildcard\" title=\"derived from a * rule\"><code>{}</code> {WILDCARD_MARK}</td>",
+                    escape_html(text)
+                )),
+                Cell::Undefined => out.push_str(&format!(
+                    "<td class=\"undefined\">{UNDEFINED_MARK}</td>"
+                )),
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("  </tbody>\n</table>\n");
+    out
+}
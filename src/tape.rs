@@ -0,0 +1,326 @@
+//! Tapes the simulator can run on.
+//!
+//! Positions are relative to the first input cell. Every backend reads blanks outside the
+//! cells written so far, and has a [left edge](Tape::left_edge) where the head stays when
+//! it moves left: [`VecTape`] stops on the first cell, as in Sipser's model, while
+//! [`DequeTape`] and [`RunLengthTape`] extend in both directions unless asked to be bounded. [`AnyTape`]
+//! picks the backend that matches a machine's model.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+
+use crate::constants::BLANK;
+use crate::{Direction, MachineType};
+
+pub trait Tape: Clone + Debug {
+    fn read(&self, position: i64) -> char;
+    fn write(&mut self, position: i64, symbol: char);
+    /// Leftmost position the head can reach, or `None` when the tape is unbounded on the left.
+    fn left_edge(&self) -> Option<i64>;
+    /// Leftmost and rightmost positions ever written, relative to the input start.
+    fn bounds(&self) -> (i64, i64);
+
+    /// Where the head lands moving in `direction` from `position`.
+    fn step(&self, position: i64, direction: Direction) -> i64 {
+        step(position, direction, self.left_edge())
+    }
+
+    /// Leftmost position a run of left moves can be taken from at once.
+    fn run_floor(&self) -> Option<i64> {
+        run_floor(self.left_edge())
+    }
+
+    /// Tape contents with the surrounding blanks trimmed.
+    fn contents(&self) -> String {
+        let (left, right) = self.bounds();
+        let text: String = (left..=right).map(|p| self.read(p)).collect();
+        text.trim_matches(BLANK).to_string()
+    }
+}
+
+/// Where the head lands moving in `direction` from `position` on a tape whose left edge is
+/// `left_edge`: it stays on the edge when it moves left from there. Behind [`Tape::step`],
+/// and used by the [dense simulator](crate::dense), which keeps its own tape but takes the
+/// edge from the backend for its model.
+pub fn step(position: i64, direction: Direction, left_edge: Option<i64>) -> i64 {
+    let next = match direction {
+        Direction::Left => position - 1,
+        Direction::Right => position + 1,
+        Direction::Stay => position,
+    };
+    left_edge.map_or(next, |edge| next.max(edge))
+}
+
+/// Leftmost position from which a run of left moves, each landing one cell over, can be
+/// taken at once on a tape whose left edge is `left_edge`. The move from the edge itself
+/// leaves the head in place, so a run stops one cell short of it. Behind [`Tape::run_floor`].
+pub fn run_floor(left_edge: Option<i64>) -> Option<i64> {
+    left_edge.map(|edge| edge + 1)
+}
+
+/// Cells of `input`, or a single blank when it is empty.
+fn initial_cells(input: &str) -> impl Iterator<Item = char> {
+    let blank = input.is_empty().then_some(BLANK);
+    input.chars().chain(blank)
+}
+
+/// Tape bounded on the left, for the Sipser model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VecTape {
+    cells: Vec<char>,
+}
+
+impl VecTape {
+    pub fn new(input: &str) -> Self {
+        VecTape {
+            cells: initial_cells(input).collect(),
+        }
+    }
+}
+
+impl Tape for VecTape {
+    fn read(&self, position: i64) -> char {
+        usize::try_from(position)
+            .ok()
+            .and_then(|i| self.cells.get(i).copied())
+            .unwrap_or(BLANK)
+    }
+
+    fn write(&mut self, position: i64, symbol: char) {
+        let i = usize::try_from(position).expect("write left of the first cell");
+        if i >= self.cells.len() {
+            self.cells.resize(i + 1, BLANK);
+        }
+        self.cells[i] = symbol;
+    }
+
+    fn left_edge(&self) -> Option<i64> {
+        Some(0)
+    }
+
+    fn bounds(&self) -> (i64, i64) {
+        (0, self.cells.len() as i64 - 1)
+    }
+}
+
+/// Tape that grows in both directions, for the Infinite model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DequeTape {
+    cells: VecDeque<char>,
+    origin: usize,
+}
+
+impl DequeTape {
+    pub fn new(input: &str) -> Self {
+        DequeTape {
+            cells: initial_cells(input).collect(),
+            origin: 0,
+        }
+    }
+
+    fn index(&self, position: i64) -> Option<usize> {
+        usize::try_from(position + self.origin as i64).ok()
+    }
+}
+
+impl Tape for DequeTape {
+    fn read(&self, position: i64) -> char {
+        self.index(position)
+            .and_then(|i| self.cells.get(i).copied())
+            .unwrap_or(BLANK)
+    }
+
+    fn write(&mut self, position: i64, symbol: char) {
+        while self.index(position).is_none() {
+            self.cells.push_front(BLANK);
+            self.origin += 1;
+        }
+        let i = self.index(position).unwrap_or_default();
+        if i >= self.cells.len() {
+            self.cells.resize(i + 1, BLANK);
+        }
+        self.cells[i] = symbol;
+    }
+
+    fn left_edge(&self) -> Option<i64> {
+        None
+    }
+
+    fn bounds(&self) -> (i64, i64) {
+        let left = -(self.origin as i64);
+        (left, left + self.cells.len() as i64 - 1)
+    }
+}
+
+/// Tape stored as runs of equal symbols, for sparse or repetitive contents such as long
+/// stretches of blanks or unary counters. Reads and writes take logarithmic time in the
+/// number of runs rather than constant time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunLengthTape {
+    /// Symbol of each run, keyed by its first position. A run ends where the next starts.
+    runs: BTreeMap<i64, char>,
+    /// One past the last written position.
+    end: i64,
+    bounded_left: bool,
+}
+
+impl RunLengthTape {
+    /// A tape holding `input`, bounded on the left like [`VecTape`] if `bounded_left`.
+    pub fn new(input: &str, bounded_left: bool) -> Self {
+        let mut tape = RunLengthTape {
+            runs: BTreeMap::new(),
+            end: 0,
+            bounded_left,
+        };
+        for (position, symbol) in initial_cells(input).enumerate() {
+            tape.write(position as i64, symbol);
+        }
+        tape
+    }
+
+    /// Number of runs, a measure of how well the contents compress.
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Makes `position` part of the written extent, padding with blanks.
+    fn extend_to(&mut self, position: i64) {
+        let Some((&left, &first)) = self.runs.first_key_value() else {
+            self.runs.insert(position, BLANK);
+            self.end = position + 1;
+            return;
+        };
+        if position < left {
+            if first == BLANK {
+                self.runs.remove(&left);
+            }
+            self.runs.insert(position, BLANK);
+        } else if position >= self.end {
+            if self.runs.last_key_value().is_some_and(|(_, &s)| s != BLANK) {
+                self.runs.insert(self.end, BLANK);
+            }
+            self.end = position + 1;
+        }
+    }
+}
+
+impl Tape for RunLengthTape {
+    fn read(&self, position: i64) -> char {
+        if position >= self.end {
+            return BLANK;
+        }
+        self.runs
+            .range(..=position)
+            .next_back()
+            .map_or(BLANK, |(_, &symbol)| symbol)
+    }
+
+    fn write(&mut self, position: i64, symbol: char) {
+        self.extend_to(position);
+        let old = self.read(position);
+        if old == symbol {
+            return;
+        }
+        let next = self
+            .runs
+            .range(position + 1..)
+            .next()
+            .map_or(self.end, |(&start, _)| start);
+        if position + 1 < next {
+            self.runs.insert(position + 1, old);
+        }
+        self.runs.insert(position, symbol);
+        if self.runs.get(&(position + 1)) == Some(&symbol) {
+            self.runs.remove(&(position + 1));
+        }
+        if self
+            .runs
+            .range(..position)
+            .next_back()
+            .is_some_and(|(_, &before)| before == symbol)
+        {
+            self.runs.remove(&position);
+        }
+    }
+
+    fn left_edge(&self) -> Option<i64> {
+        self.bounded_left.then_some(0)
+    }
+
+    fn bounds(&self) -> (i64, i64) {
+        let left = self.runs.first_key_value().map_or(0, |(&left, _)| left);
+        (left, self.end - 1)
+    }
+
+    fn contents(&self) -> String {
+        let mut text = String::new();
+        let mut runs = self.runs.iter().peekable();
+        while let Some((&start, &symbol)) = runs.next() {
+            let end = runs.peek().map_or(self.end, |&(&next, _)| next);
+            text.extend(std::iter::repeat_n(symbol, (end - start) as usize));
+        }
+        text.trim_matches(BLANK).to_string()
+    }
+}
+
+/// The backend matching a machine's model, or a run-length tape with the same semantics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyTape {
+    Bounded(VecTape),
+    TwoWay(DequeTape),
+    RunLength(RunLengthTape),
+}
+
+impl AnyTape {
+    /// [`VecTape`] for models bounded on the left such as Sipser's, [`DequeTape`] for the
+    /// others.
+    pub fn for_model(model: MachineType, input: &str) -> Self {
+        if model.bounded_left() {
+            AnyTape::Bounded(VecTape::new(input))
+        } else {
+            AnyTape::TwoWay(DequeTape::new(input))
+        }
+    }
+
+    /// A [`RunLengthTape`], bounded on the left if the model is.
+    pub fn run_length(model: MachineType, input: &str) -> Self {
+        AnyTape::RunLength(RunLengthTape::new(input, model.bounded_left()))
+    }
+}
+
+/// Calls the same method on whichever backend `$tape` holds.
+macro_rules! dispatch {
+    ($tape:expr, $inner:ident => $call:expr) => {
+        match $tape {
+            AnyTape::Bounded($inner) => $call,
+            AnyTape::TwoWay($inner) => $call,
+            AnyTape::RunLength($inner) => $call,
+        }
+    };
+}
+
+impl Tape for AnyTape {
+    fn read(&self, position: i64) -> char {
+        dispatch!(self, tape => tape.read(position))
+    }
+
+    fn write(&mut self, position: i64, symbol: char) {
+        dispatch!(self, tape => tape.write(position, symbol))
+    }
+
+    fn left_edge(&self) -> Option<i64> {
+        dispatch!(self, tape => tape.left_edge())
+    }
+
+    fn bounds(&self) -> (i64, i64) {
+        dispatch!(self, tape => tape.bounds())
+    }
+
+    fn contents(&self) -> String {
+        dispatch!(self, tape => tape.contents())
+    }
+}
@@ -0,0 +1,129 @@
+//! Checks a conversion by running the source and converted machines side by side.
+//!
+//! Both machines must end the same way on every input: halting in the same state with the
+//! same tape contents, or getting stuck. The wall markers written by the conversion are
+//! ignored when comparing tapes. Runs that hit the step limit are reported as inconclusive.
+
+use std::fmt::{Display, Formatter};
+
+use crate::constants::{BLANK, LEFT_WALL, RIGHT_WALL};
+use crate::simulator::{Outcome, Simulator};
+use crate::tape::Tape;
+use crate::{ConversionError, Machine, convert};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Match,
+    Mismatch,
+    Inconclusive,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Check {
+    pub input: String,
+    pub verdict: Verdict,
+    pub source: (Outcome, String),
+    pub converted: (Outcome, String),
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let verdict = match self.verdict {
+            Verdict::Match => "ok",
+            Verdict::Mismatch => "MISMATCH",
+            Verdict::Inconclusive => "inconclusive",
+        };
+        write!(f, "{verdict:>12}  '{}'", self.input)?;
+        if self.verdict != Verdict::Match {
+            write!(
+                f,
+                "\n              source:    {} with tape '{}'\n              converted: {} with tape '{}'",
+                self.source.0, self.source.1, self.converted.0, self.converted.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn strip_walls(contents: &str) -> String {
+    contents
+        .chars()
+        .filter(|&c| c != LEFT_WALL && c != RIGHT_WALL)
+        .collect::<String>()
+        .trim_matches(BLANK)
+        .to_string()
+}
+
+fn same_ending(source: &Outcome, converted: &Outcome) -> bool {
+    match (source, converted) {
+        (Outcome::Halted(a), Outcome::Halted(b)) => a == b,
+        (Outcome::Stuck { .. }, Outcome::Stuck { .. }) => true,
+        _ => false,
+    }
+}
+
+/// Every string over `alphabet` of length at most `max_length`, shortest first.
+pub fn enumerate_inputs(alphabet: &[char], max_length: usize) -> Vec<String> {
+    let mut inputs = vec![String::new()];
+    let mut previous = vec![String::new()];
+    for _ in 0..max_length {
+        previous = previous
+            .iter()
+            .flat_map(|prefix| alphabet.iter().map(move |c| format!("{prefix}{c}")))
+            .collect();
+        inputs.extend(previous.iter().cloned());
+    }
+    inputs
+}
+
+/// Runs `machine` and its conversion to the default target on every input. Fails when the
+/// standard registry has no conversion for its model.
+pub fn verify(
+    machine: &Machine,
+    inputs: &[String],
+    max_steps: u64,
+) -> Result<Vec<Check>, ConversionError> {
+    let converted = convert(machine)?;
+    Ok(verify_against(machine, &converted, inputs, max_steps))
+}
+
+/// Runs `machine` and `converted`, a conversion of it to any model, on every input.
+pub fn verify_against(
+    machine: &Machine,
+    converted: &Machine,
+    inputs: &[String],
+    max_steps: u64,
+) -> Vec<Check> {
+    inputs
+        .iter()
+        .map(|input| {
+            let mut source_sim = Simulator::new(machine, input);
+            let source_outcome = source_sim.run(max_steps);
+            let source_tape = source_sim.config.tape.contents();
+
+            let mut converted_sim = Simulator::new(converted, input);
+            // The conversion adds a constant factor per step plus a sweep over the tape for
+            // every shift, so give it a proportionally larger budget.
+            let converted_outcome = converted_sim.run(max_steps.saturating_mul(100));
+            let converted_tape = strip_walls(&converted_sim.config.tape.contents());
+
+            let verdict = if source_outcome == Outcome::StepLimit
+                || converted_outcome == Outcome::StepLimit
+            {
+                Verdict::Inconclusive
+            } else if same_ending(&source_outcome, &converted_outcome)
+                && strip_walls(&source_tape) == converted_tape
+            {
+                Verdict::Match
+            } else {
+                Verdict::Mismatch
+            };
+            Check {
+                input: input.clone(),
+                verdict,
+                source: (source_outcome, source_tape),
+                converted: (converted_outcome, converted_tape),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,226 @@
+//! Polls machine files and re-converts, re-lints and re-tests them whenever they change.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use turing::lint::{self, Severity};
+use turing::verify::{self, Verdict};
+use turing::{ConversionError, ExportFormat, MachineType, ParseMode, convert_to, read_machine};
+
+const MAX_DIFF_LINES: usize = 20;
+/// Above this many changed lines (after trimming the common prefix and suffix) the diff only
+/// reports counts, to keep the quadratic LCS table small.
+const MAX_DIFF_WINDOW: usize = 4000;
+
+pub struct WatchOptions {
+    pub target: Option<MachineType>,
+    pub inputs: Vec<String>,
+    pub max_steps: u64,
+    pub interval: Duration,
+    pub mode: ParseMode,
+    /// Write each conversion to `<name>.out` next to its source.
+    pub write: bool,
+}
+
+#[derive(Default)]
+struct Watched {
+    /// Modification time when the file was last processed, `None` if it was missing.
+    modified: Option<SystemTime>,
+    /// Last conversion that succeeded, to diff the next one against.
+    output: Option<String>,
+    /// What the last run printed, errors included; `None` until the first run.
+    report: Option<String>,
+}
+
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+enum Edit<'a> {
+    Keep,
+    Add(&'a str),
+    Remove(&'a str),
+}
+
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Option<Vec<Edit<'a>>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    if a.len() > MAX_DIFF_WINDOW || b.len() > MAX_DIFF_WINDOW {
+        return None;
+    }
+
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            edits.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            edits.push(Edit::Add(b[j]));
+            j += 1;
+        } else {
+            edits.push(Edit::Remove(a[i]));
+            i += 1;
+        }
+    }
+    Some(edits)
+}
+
+fn render_diff(old: &str, new: &str) -> String {
+    let Some(edits) = diff_lines(old, new) else {
+        return format!(
+            "  diff: too large to show ({} -> {} lines)\n",
+            old.lines().count(),
+            new.lines().count()
+        );
+    };
+    let changes: Vec<String> = edits
+        .iter()
+        .filter_map(|e| match e {
+            Edit::Keep => None,
+            Edit::Add(line) => Some(format!("    + {line}")),
+            Edit::Remove(line) => Some(format!("    - {line}")),
+        })
+        .collect();
+    if changes.is_empty() {
+        return "  diff: output unchanged\n".to_string();
+    }
+    let added = edits.iter().filter(|e| matches!(e, Edit::Add(_))).count();
+    let mut out = format!("  diff: +{} -{}\n", added, changes.len() - added);
+    for line in changes.iter().take(MAX_DIFF_LINES) {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if changes.len() > MAX_DIFF_LINES {
+        out.push_str(&format!(
+            "    ... {} more\n",
+            changes.len() - MAX_DIFF_LINES
+        ));
+    }
+    out
+}
+
+fn process(path: &Path, watched: &mut Watched, options: &WatchOptions) -> String {
+    let name = path.display();
+    let Some(input) = path.to_str() else {
+        return format!("[{}] {name}: path is not valid UTF-8\n", timestamp());
+    };
+    let machine = match read_machine(input, options.mode) {
+        Ok(machine) => machine,
+        Err(e @ ConversionError::ParseError(_)) => {
+            return format!("[{}] {name}: parse failed\n{e}\n", timestamp());
+        }
+        Err(e) => return format!("[{}] {name}: {e}\n", timestamp()),
+    };
+    let (converted, rendered) = match convert_to(&machine, options.target, ExportFormat::Text) {
+        Ok(result) => result,
+        Err(e) => return format!("[{}] {name}: {e}\n", timestamp()),
+    };
+    let output_path = path.with_extension("out");
+    let written = if !options.write || output_path == path {
+        String::new()
+    } else {
+        match fs::write(&output_path, &rendered) {
+            Ok(()) => format!(" -> {}", output_path.display()),
+            Err(e) => format!(" (could not write {}: {e})", output_path.display()),
+        }
+    };
+
+    let mut out = format!(
+        "[{}] {name}: {} states, {} transitions{written}\n",
+        timestamp(),
+        converted.states().len(),
+        converted.rules.len()
+    );
+
+    for warning in &machine.warnings {
+        out.push_str(&format!("{warning}\n"));
+    }
+
+    let lints = lint::check(&machine);
+    let errors = lints
+        .iter()
+        .filter(|l| l.severity == Severity::Error)
+        .count();
+    out.push_str(&format!(
+        "  lint: {} errors, {} warnings\n",
+        errors,
+        lints.len() - errors
+    ));
+    for l in &lints {
+        out.push_str(&format!("    {l}\n"));
+    }
+
+    let inputs: Vec<String> = options
+        .inputs
+        .iter()
+        .cloned()
+        .chain(machine.initial_input.clone())
+        .collect();
+    if !inputs.is_empty() && converted.model != machine.model {
+        let checks = verify::verify_against(&machine, &converted, &inputs, options.max_steps);
+        let ok = checks
+            .iter()
+            .filter(|c| c.verdict == Verdict::Match)
+            .count();
+        out.push_str(&format!("  tests: {ok}/{} ok\n", checks.len()));
+        for check in checks.iter().filter(|c| c.verdict != Verdict::Match) {
+            out.push_str(&format!("  {check}\n"));
+        }
+    }
+
+    if let Some(previous) = &watched.output {
+        out.push_str(&render_diff(previous, &rendered));
+    }
+    watched.output = Some(rendered);
+    out
+}
+
+pub fn watch(paths: &[PathBuf], options: &WatchOptions) -> ! {
+    let mut watched: HashMap<&Path, Watched> = HashMap::new();
+    loop {
+        for path in paths {
+            let entry = watched.entry(path).or_default();
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            // A broken or missing file is reported once, then again only when it changes.
+            if entry.report.is_none() || modified != entry.modified {
+                entry.modified = modified;
+                let report = process(path, entry, options);
+                print!("{report}");
+                entry.report = Some(report);
+            }
+        }
+        thread::sleep(options.interval);
+    }
+}
@@ -0,0 +1,48 @@
+//! Converting directories of machines.
+
+use std::fs;
+use std::path::PathBuf;
+
+use turing::batch::run_batch;
+use turing::{ConversionError, ExportFormat, ParseMode};
+
+const EXAMPLE: &str = include_str!("../example.in");
+
+/// An empty directory for `name` under the test scratch directory.
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn unwritable_output_directories_fail_only_their_files() {
+    let dir = scratch("batch-unwritable");
+    fs::write(dir.join("a.in"), EXAMPLE).unwrap();
+    fs::create_dir(dir.join("sub")).unwrap();
+    fs::write(dir.join("sub/b.in"), EXAMPLE).unwrap();
+    let output = dir.join("out");
+    fs::create_dir(&output).unwrap();
+    // A file where the output subdirectory should go.
+    fs::write(output.join("sub"), "").unwrap();
+
+    let entries = run_batch(&dir, &output, None, ExportFormat::Text, ParseMode::Strict).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].result.is_ok());
+    assert!(matches!(entries[1].result, Err(ConversionError::Io(_))));
+}
+
+#[test]
+fn json_output_next_to_the_inputs_is_not_converted_again() {
+    let dir = scratch("batch-in-place");
+    fs::write(dir.join("a.in"), EXAMPLE).unwrap();
+    for _ in 0..2 {
+        let entries = run_batch(&dir, &dir, None, ExportFormat::Json, ParseMode::Strict).unwrap();
+        let inputs: Vec<_> = entries.iter().map(|e| e.input.clone()).collect();
+        assert_eq!(inputs, [dir.join("a.in")]);
+    }
+    assert!(dir.join("a.out.json").exists());
+}
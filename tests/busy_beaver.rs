@@ -0,0 +1,43 @@
+//! The busy beaver standard notation.
+
+use turing::busy_beaver::{BusyBeaverError, parse, to_notation};
+use turing::tm;
+
+const BB4: &str = "1RB1LB_1LA0LC_1RZ1LD_1RD0RA";
+
+#[test]
+fn champion_round_trips() {
+    let machine = parse(BB4).unwrap();
+    assert_eq!(machine.start_state, "A");
+    assert_eq!(machine.rules.len(), 8);
+    assert_eq!(to_notation(&machine).unwrap(), BB4);
+    assert_eq!(parse(&to_notation(&machine).unwrap()).unwrap(), machine);
+}
+
+#[test]
+fn undefined_transitions_round_trip() {
+    let notation = "1RB---_0LA1RZ";
+    assert_eq!(to_notation(&parse(notation).unwrap()).unwrap(), notation);
+}
+
+#[test]
+fn empty_notation_is_an_error() {
+    assert_eq!(parse(""), Err(BusyBeaverError::Empty));
+    assert_eq!(parse(" \n"), Err(BusyBeaverError::Empty));
+}
+
+#[test]
+fn two_rules_for_one_symbol_are_an_error() {
+    let machine = tm!(Infinite:
+        0 '_' => '1', R, 0;
+        0 '1' => '1', L, halt;
+        0 '_' => '1', L, halt;
+    );
+    assert_eq!(
+        to_notation(&machine),
+        Err(BusyBeaverError::DuplicateRule {
+            state: "0".to_string(),
+            symbol: '_',
+        })
+    );
+}
@@ -0,0 +1,78 @@
+//! `DenseSimulator`, with and without macro-steps, against the step-by-step `Simulator`.
+
+use turing::dense::DenseSimulator;
+use turing::simulator::Simulator;
+use turing::snapshot::{Snapshot, SnapshotError};
+use turing::tape::Tape;
+use turing::{Machine, busy_beaver, tm};
+
+const BB4: &str = "1RB1LB_1LA0LC_1RZ1LD_1RD0RA";
+
+/// Fails unless both dense simulators agree with `Simulator` after every step limit up to
+/// `max_steps`.
+fn assert_same_runs(machine: &Machine, input: &str, max_steps: u64) {
+    for limit in 0..=max_steps {
+        let mut simulator = Simulator::new(machine, input);
+        let outcome = simulator.run(limit);
+        for macro_steps in [true, false] {
+            let mut dense = DenseSimulator::new(machine, input);
+            dense.macro_steps = macro_steps;
+            let context = format!("limit {limit}, macro-steps {macro_steps}");
+            assert_eq!(dense.run(limit), outcome, "{context}");
+            assert_eq!(dense.steps, simulator.config.steps, "{context}");
+            assert_eq!(dense.state(), simulator.state(), "{context}");
+            assert_eq!(dense.head(), simulator.config.head, "{context}");
+            assert_eq!(
+                dense.contents(),
+                simulator.config.tape.contents(),
+                "{context}"
+            );
+        }
+    }
+}
+
+#[test]
+fn busy_beaver_champion_matches() {
+    let machine = busy_beaver::parse(BB4).unwrap();
+    assert_same_runs(&machine, "", 110);
+}
+
+#[test]
+fn sweeps_into_the_left_wall_match() {
+    // Writes a run of 1s, then sweeps it leftwards in one state, past the first cell.
+    let machine = tm!(Sipser:
+        0 '_' => '1', R, 1;
+        1 '_' => '1', R, 2;
+        2 '_' => '1', R, 3;
+        3 '_' => '_', L, 4;
+        4 '1' => 'x', L, 4;
+        4 'x' => 'y', R, halt;
+    );
+    assert_same_runs(&machine, "", 12);
+    let machine = tm!(Sipser:
+        0 '1' => '1', R, 0;
+        0 '_' => '_', L, 1;
+        1 '1' => '1', L, 1;
+        1 '0' => '0', R, halt;
+    );
+    assert_same_runs(&machine, "1111", 20);
+}
+
+#[test]
+fn snapshots_must_cover_the_first_cell() {
+    let machine = busy_beaver::parse(BB4).unwrap();
+    let mut simulator = Simulator::new(&machine, "");
+    simulator.run(20);
+    let snapshot = Snapshot {
+        left: 1,
+        ..simulator.snapshot()
+    };
+    assert!(matches!(
+        simulator.restore(&snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+    assert!(matches!(
+        DenseSimulator::resume(&machine, &snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+}
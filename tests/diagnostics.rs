@@ -0,0 +1,98 @@
+//! Parse errors and warnings of the text format.
+
+use turing::diagnostics::{Diagnostic, render};
+use turing::lint::Severity;
+use turing::{ConversionError, Direction, ParseMode, parse_line, parse_machine};
+
+fn errors(text: &str) -> Vec<Diagnostic> {
+    match parse_machine(text, ParseMode::Strict) {
+        Err(ConversionError::ParseError(diagnostics)) => diagnostics,
+        other => panic!("expected parse errors, got {other:?}"),
+    }
+}
+
+#[test]
+fn reports_every_bad_line_at_once() {
+    let diagnostics = errors(";I\n0 0 1 x 1\n0 1 1 r 1\n0 _ 1\n1 0 1 r halt extra\n");
+    let located: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.line, d.column(), d.span.clone()))
+        .collect();
+    assert_eq!(located, [(2, 7, 6..7), (4, 5, 4..5), (5, 14, 13..18)]);
+    assert!(render(&diagnostics).ends_with("error: 3 lines failed to parse"));
+}
+
+#[test]
+fn points_a_caret_at_the_offending_token() {
+    let diagnostics = errors(";I\n; comment\n0 0 1 x 1\n");
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "error: Invalid direction: x, expected l, r or *\n --> line 3:7\n  |\n3 | 0 0 1 x 1\n  |       ^"
+    );
+}
+
+#[test]
+fn too_few_parts_point_at_the_last_token() {
+    let error = parse_line("0 0 1 r", ParseMode::Strict).unwrap_err();
+    assert_eq!(error.span, 6..7);
+    let diagnostics = errors(";I\n0 0 1 right\n");
+    assert!(
+        diagnostics[0]
+            .to_string()
+            .ends_with("0 0 1 right\n  |       ^^^^^")
+    );
+}
+
+#[test]
+fn suggests_a_direction() {
+    let diagnostics = errors(";I\n0 0 1 R 1\n0 1 1 left 1\n0 _ 1 up 1\n");
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Invalid direction: R, expected l, r or * (did you mean 'r'?)",
+            "Invalid direction: left, expected l, r or * (did you mean 'l'?)",
+            "Invalid direction: up, expected l, r or *",
+        ]
+    );
+}
+
+#[test]
+fn multi_character_symbols_are_underlined() {
+    let diagnostics = errors(";I\n0 ab 1 r 1\n");
+    assert_eq!(
+        diagnostics[0].message,
+        "Invalid symbol, must be a single char: 'ab'"
+    );
+    assert!(diagnostics[0].to_string().ends_with("|   ^^"));
+}
+
+#[test]
+fn warns_about_non_breaking_spaces() {
+    let machine = parse_machine(";I\n0 0 1\u{a0}r 1\n", ParseMode::Strict).unwrap();
+    let [warning] = machine.warnings.as_slice() else {
+        panic!("expected one warning, got {:?}", machine.warnings);
+    };
+    assert_eq!(warning.severity, Severity::Warning);
+    assert_eq!(warning.message, "Unusual whitespace character U+00A0");
+    assert_eq!((warning.line, warning.column()), (2, 6));
+}
+
+#[test]
+fn warnings_are_reported_with_errors() {
+    let diagnostics = errors(";I\n0 0 1\u{a0}r 1\n0 0 1 x 1\n");
+    let severities: Vec<_> = diagnostics.iter().map(|d| d.severity).collect();
+    assert_eq!(severities, [Severity::Warning, Severity::Error]);
+    assert!(render(&diagnostics).starts_with("warning: "));
+}
+
+#[test]
+fn lenient_mode_accepts_what_strict_mode_rejects() {
+    let text = ";I\n0 ab 1 R 1\n1 0 1 L halt\n";
+    assert_eq!(errors(text).len(), 2);
+    let machine = parse_machine(text, ParseMode::Lenient).unwrap();
+    let first = &machine.rules[0].transition;
+    assert_eq!(first.current_symbol, 'a');
+    assert_eq!(first.direction, Direction::Right);
+    assert_eq!(machine.rules[1].transition.direction, Direction::Left);
+}
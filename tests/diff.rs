@@ -0,0 +1,122 @@
+//! `turing diff`, comparing machines up to a renaming of their states.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use turing::diff::{Change, diff};
+use turing::{Direction, Machine, Transition, tm};
+
+fn pair(a: &str, b: &str) -> (String, String) {
+    (a.to_string(), b.to_string())
+}
+
+#[test]
+fn renamed_states_are_paired() {
+    let old = tm!(Infinite:
+        0 '0' => '1', R, sim_3;
+        sim_3 '1' => '0', L, sim_3;
+        sim_3 '_' => '_', R, halt;
+    );
+    let new = tm!(Infinite:
+        0 '0' => '1', R, q3;
+        q3 '1' => '0', L, q3;
+        q3 '_' => '_', R, halt;
+    );
+    let result = diff(&old, &new);
+    assert!(result.is_isomorphic());
+    assert_eq!(
+        result.renamed().cloned().collect::<Vec<_>>(),
+        [pair("sim_3", "q3")]
+    );
+}
+
+#[test]
+fn changed_rules_are_reported_under_the_new_names() {
+    let old = tm!(Infinite:
+        0 '0' => '1', R, sim_3;
+        sim_3 '1' => '0', L, halt;
+    );
+    let new = tm!(Infinite:
+        0 '0' => '1', R, q3;
+        q3 '1' => '1', L, halt;
+        q3 '_' => '1', L, halt;
+    );
+    let result = diff(&old, &new);
+    assert_eq!(
+        result.changes,
+        [
+            Change::Changed {
+                old: Transition::new("sim_3", '1', '0', Direction::Left, "halt"),
+                new: Transition::new("q3", '1', '1', Direction::Left, "halt"),
+            },
+            Change::Added(Transition::new("q3", '_', '1', Direction::Left, "halt")),
+        ]
+    );
+}
+
+#[test]
+fn the_lockstep_walk_wins_over_names_and_shapes() {
+    // `a` and `b` have the same shape, and the new machine swaps their names.
+    let old = tm!(Infinite:
+        0 '0' => '0', R, a;
+        0 '1' => '1', R, b;
+        a '0' => '1', R, halt;
+        b '0' => '1', L, halt;
+    );
+    let new = tm!(Infinite:
+        0 '0' => '0', R, b;
+        0 '1' => '1', R, a;
+        b '0' => '1', R, halt;
+        a '0' => '1', L, halt;
+    );
+    let result = diff(&old, &new);
+    assert!(result.is_isomorphic());
+    assert!(result.mapping.contains(&pair("a", "b")));
+    assert!(result.mapping.contains(&pair("b", "a")));
+}
+
+#[test]
+fn a_star_write_equals_writing_the_symbol_read() {
+    let old = tm!(Infinite: 0 '1' => '*', R, *);
+    let new = tm!(Infinite: 0 '1' => '1', R, 0);
+    assert!(diff(&old, &new).is_isomorphic());
+}
+
+/// Runs `turing diff` on `old` and `new`, returning its exit code and output.
+fn run_diff(name: &str, old: &Machine, new: &Machine) -> (Option<i32>, String) {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let old_path = dir.join(format!("{name}.old.in"));
+    let new_path = dir.join(format!("{name}.new.in"));
+    fs::write(&old_path, old.to_string()).unwrap();
+    fs::write(&new_path, new.to_string()).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_turing"))
+        .arg("diff")
+        .arg(&old_path)
+        .arg(&new_path)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn exit_code_tells_whether_the_machines_match() {
+    let old = tm!(Infinite: 0 '0' => '1', R, sim_1; sim_1 '_' => '_', L, halt);
+    let renamed = tm!(Infinite: 0 '0' => '1', R, q1; q1 '_' => '_', L, halt);
+    let changed = tm!(Infinite: 0 '0' => '0', R, q1; q1 '_' => '_', L, halt);
+
+    let (code, out) = run_diff("renamed", &old, &renamed);
+    assert_eq!(code, Some(0));
+    assert!(out.contains("  sim_1 -> q1\n"), "{out}");
+    assert!(
+        out.ends_with("identical up to renaming (1 states renamed)\n"),
+        "{out}"
+    );
+
+    let (code, out) = run_diff("changed", &old, &changed);
+    assert_eq!(code, Some(1));
+    assert!(out.ends_with("0 added, 0 removed, 1 changed\n"), "{out}");
+}
@@ -0,0 +1,34 @@
+//! Round trips through the binary encoding.
+
+use turing::encoding::{decode, encode};
+use turing::{Machine, MachineType, ParseMode, parse_machine, tm};
+
+const EXAMPLE: &str = include_str!("../example.in");
+
+fn assert_round_trip(encoded: &str) {
+    let decoded = decode(encoded).unwrap();
+    assert_eq!(encode(&decoded).unwrap(), encoded);
+}
+
+#[test]
+fn example_round_trips() {
+    let machine = parse_machine(EXAMPLE, ParseMode::Strict).unwrap();
+    assert_round_trip(&encode(&machine).unwrap());
+}
+
+#[test]
+fn round_trips_with_ten_or_more_halt_states() {
+    for halts in [9, 10, 12, 100] {
+        let transitions = (0..halts)
+            .flat_map(|i| {
+                tm![
+                    (i) '0' => '0', R, (i + 1);
+                    (i) '1' => '1', R, (format!("halt_{i}"));
+                ]
+            })
+            .collect();
+        let machine = Machine::from_transitions(MachineType::Infinite, transitions);
+        let encoded = encode(&machine).unwrap();
+        assert_round_trip(&encoded);
+    }
+}
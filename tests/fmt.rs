@@ -0,0 +1,38 @@
+//! Golden tests for `turing fmt`.
+
+use turing::format::{format, lost_comments};
+use turing::{ParseMode, parse_machine};
+
+const EXAMPLE: &str = include_str!("../example.in");
+const EXAMPLE_FORMATTED: &str = include_str!("golden/example.fmt");
+
+#[test]
+fn formats_example_like_the_golden_file() {
+    let machine = parse_machine(EXAMPLE, ParseMode::Strict).unwrap();
+    assert_eq!(format(&machine), EXAMPLE_FORMATTED);
+}
+
+#[test]
+fn formatting_keeps_every_comment_of_the_example() {
+    let machine = parse_machine(EXAMPLE, ParseMode::Strict).unwrap();
+    assert_eq!(
+        lost_comments(EXAMPLE, &format(&machine)),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let machine = parse_machine(EXAMPLE_FORMATTED, ParseMode::Strict).unwrap();
+    assert_eq!(format(&machine), EXAMPLE_FORMATTED);
+}
+
+#[test]
+fn reports_dropped_comments() {
+    let original = ";I\n; kept\n0 0 1 r 0 ; inline\n; dropped\n";
+    let formatted = ";I\n; kept\n0 0 1 r * ; inline\n";
+    assert_eq!(
+        lost_comments(original, formatted),
+        vec!["dropped".to_string()]
+    );
+}
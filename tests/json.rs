@@ -0,0 +1,87 @@
+//! Import and export of machines as JSON.
+
+use turing::json::{MAX_DEPTH, machine_from_json, machine_to_json, parse};
+use turing::simulator::{Outcome, Simulator};
+use turing::{ParseMode, parse_machine, tm};
+
+const EXAMPLE: &str = include_str!("../example.in");
+
+#[test]
+fn text_to_json_and_back_keeps_the_machine() {
+    let machine = parse_machine(EXAMPLE, ParseMode::Strict).unwrap();
+    let document = machine_to_json(&machine).to_string();
+    let restored = machine_from_json(&parse(&document).unwrap()).unwrap();
+    assert_eq!(restored.to_string(), machine.to_string());
+    assert_eq!(machine_to_json(&restored).to_string(), document);
+}
+
+#[test]
+fn text_to_json_and_back_only_changes_the_layout() {
+    let text = ";I\n; header\n0 0 0 r 0\n\n; detached\n\n; lead\n0 1 1 l halt ! ; inline\n";
+    let machine = parse_machine(text, ParseMode::Strict).unwrap();
+    let restored = machine_from_json(&machine_to_json(&machine)).unwrap();
+    assert_eq!(
+        restored.to_string(),
+        ";I\n; header\n\n0 0 * r *\n; detached\n\n; lead\n0 1 * l halt ! ; inline\n"
+    );
+}
+
+#[test]
+fn built_machines_round_trip() {
+    let machine = tm!(Sipser:
+        q0 '0' => '1', R, q1;
+        q1 '_' => '_', L, halt;
+        q1 '*' => '*', R, *;
+    );
+    let restored = machine_from_json(&machine_to_json(&machine)).unwrap();
+    assert_eq!(restored, machine);
+}
+
+#[test]
+fn declared_halt_states_must_be_named_like_halt_states() {
+    let document = r#"{
+        "model": "infinite",
+        "halt_states": ["accept"],
+        "transitions": [{ "from": "0", "read": "_", "write": "1", "move": "r", "to": "accept" }]
+    }"#;
+    let error = machine_from_json(&parse(document).unwrap()).unwrap_err();
+    assert!(error.to_string().contains("\"accept\""), "{error}");
+}
+
+#[test]
+fn declared_halt_states_halt_when_simulated() {
+    let document = r#"{
+        "model": "infinite",
+        "halt_states": ["halt_accept", "halt_reject"],
+        "transitions": [
+            { "from": "0", "read": "1", "write": "1", "move": "r", "to": "halt_accept" },
+            { "from": "0", "read": "0", "write": "0", "move": "r", "to": "halt_reject" }
+        ]
+    }"#;
+    let machine = machine_from_json(&parse(document).unwrap()).unwrap();
+    assert!(machine.halt_states.contains("halt_reject"));
+    let outcome = Simulator::new(&machine, "1").run(10);
+    assert_eq!(outcome, Outcome::Halted("halt_accept".to_string()));
+}
+
+#[test]
+fn schema_errors_have_no_position() {
+    let error = machine_from_json(&parse(r#"{ "model": "infinite" }"#).unwrap()).unwrap_err();
+    assert_eq!(error.position, None);
+    assert_eq!(error.to_string(), "missing field \"transitions\"");
+}
+
+#[test]
+fn syntax_errors_point_at_the_input() {
+    let error = parse("{\n  \"model\" \"infinite\"\n}").unwrap_err();
+    assert_eq!(error.position, Some((2, 12)));
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    assert!(parse(&"[".repeat(MAX_DEPTH)).is_err());
+    let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+    assert!(parse(&nested).is_ok());
+    let error = parse(&"{\"a\":[".repeat(100_000)).unwrap_err();
+    assert!(error.message.contains("nesting"), "{error}");
+}
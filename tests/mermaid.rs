@@ -0,0 +1,38 @@
+//! Mermaid state diagrams.
+
+use turing::mermaid::to_mermaid;
+use turing::tm;
+
+#[test]
+fn quotes_in_state_names_are_escaped() {
+    let machine = tm!(Infinite:
+        (r#"say "hi""#) '_' => '1', R, halt;
+    );
+    let diagram = to_mermaid(&machine, false);
+    assert!(
+        diagram.contains("state \"say #34;hi#34;\" as s1\n"),
+        "{diagram}"
+    );
+}
+
+#[test]
+fn any_state_rules_are_drawn_on_each_state() {
+    let machine = tm!(Infinite:
+        0 '1' => '0', R, 1;
+        1 '*' => '*', L, halt;
+        * '1' => '1', R, *;
+        * '_' => '1', L, 0;
+    );
+    let diagram = to_mermaid(&machine, false);
+    assert!(!diagram.contains("\"*\""), "{diagram}");
+    let edges: Vec<&str> = diagram.lines().filter(|l| l.contains(" : ")).collect();
+    assert_eq!(
+        edges,
+        [
+            "    s0 --> s1 : 1/0,r",
+            "    s1 --> s2 : */*,l",
+            "    s0 --> s0 : _/1,l",
+        ],
+        "{diagram}"
+    );
+}
@@ -0,0 +1,127 @@
+//! A tape model registered from outside the crate.
+
+use turing::encoding::{decode_with, encode_with};
+use turing::json::{machine_from_json_with, machine_to_json};
+use turing::model::{Conversion, Registry, TapeModel};
+use turing::simulator::{Outcome, Simulator};
+use turing::{ExportFormat, Machine, MachineType, ParseMode, convert_with, parse_input_with, tm};
+
+/// A doubly infinite tape under another name, so conversions to it only relabel the machine.
+struct Labelled;
+
+impl TapeModel for Labelled {
+    fn key(&self) -> &'static str {
+        "labelled"
+    }
+
+    fn header(&self) -> &'static str {
+        ";L"
+    }
+
+    fn bounded_left(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "Labelled"
+    }
+
+    fn banner(&self) -> &'static str {
+        "; Converted to a labelled tape"
+    }
+}
+
+static LABELLED: MachineType = MachineType::new(&Labelled);
+
+struct Relabel {
+    from: MachineType,
+    to: MachineType,
+}
+
+impl Conversion for Relabel {
+    fn source(&self) -> MachineType {
+        self.from
+    }
+
+    fn target(&self) -> MachineType {
+        self.to
+    }
+
+    fn convert(&self, machine: &Machine) -> Machine {
+        let mut converted = machine.clone();
+        converted.model = self.to;
+        converted
+    }
+}
+
+fn registry() -> Registry {
+    let mut registry = Registry::standard();
+    registry.register_model(LABELLED);
+    registry.register_conversion(Relabel {
+        from: LABELLED,
+        to: MachineType::Infinite,
+    });
+    registry.register_conversion(Relabel {
+        from: MachineType::Infinite,
+        to: LABELLED,
+    });
+    registry
+}
+
+#[test]
+fn custom_models_are_found_by_header_key_and_number() {
+    let registry = registry();
+    let text = ";L\n0 _ 1 r halt\n";
+    let machine = parse_input_with(&registry, text, None, ParseMode::Strict).unwrap();
+    assert_eq!(machine.model, LABELLED);
+    assert!(parse_input_with(&Registry::standard(), text, None, ParseMode::Strict).is_err());
+
+    let json = machine_from_json_with(&registry, &machine_to_json(&machine)).unwrap();
+    assert_eq!(json, machine);
+
+    let encoded = encode_with(&registry, &machine).unwrap();
+    assert!(encoded.starts_with("0001"));
+    assert_eq!(decode_with(&registry, &encoded).unwrap().model, LABELLED);
+}
+
+#[test]
+fn custom_models_convert_through_the_shortest_path() {
+    let registry = registry();
+    let path = registry.path(LABELLED, MachineType::Sipser).unwrap();
+    let steps: Vec<_> = path.iter().map(|c| (c.source(), c.target())).collect();
+    assert_eq!(
+        steps,
+        [
+            (LABELLED, MachineType::Infinite),
+            (MachineType::Infinite, MachineType::Sipser),
+        ]
+    );
+
+    let machine = tm!(Infinite:
+        0 '1' => '1', L, 0;
+        0 '_' => '1', R, halt;
+    );
+    let labelled = registry.convert(&machine, Some(LABELLED)).unwrap();
+    assert_eq!(labelled.model, LABELLED);
+    let (converted, rendered) = convert_with(
+        &registry,
+        &labelled,
+        Some(MachineType::Sipser),
+        ExportFormat::Text,
+    )
+    .unwrap();
+    assert_eq!(converted.model, MachineType::Sipser);
+    assert!(rendered.starts_with(MachineType::Sipser.model().banner()));
+    let outcome = Simulator::new(&converted, "11").run(1_000);
+    assert!(matches!(outcome, Outcome::Halted(_)), "{outcome:?}");
+}
+
+#[test]
+fn machine_types_are_equal_by_key() {
+    assert_eq!(
+        MachineType::new(&turing::model::Infinite),
+        MachineType::Infinite
+    );
+    assert_ne!(LABELLED, MachineType::Infinite);
+    assert_eq!(LABELLED.header(), ";L");
+}
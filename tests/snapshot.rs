@@ -0,0 +1,98 @@
+//! Saving and resuming runs.
+
+use std::fs;
+use std::path::PathBuf;
+
+use turing::json;
+use turing::snapshot::{Snapshot, SnapshotError, machine_hash};
+use turing::{ParseMode, parse_machine, tm};
+
+#[test]
+fn step_counts_beyond_double_precision_survive_json() {
+    let snapshot = Snapshot {
+        machine: 0x9c1f_0e4b_7a2d_5e83,
+        state: "B".to_string(),
+        head: -2,
+        steps: (1 << 53) + 1,
+        left: -3,
+        tape: "1_11".to_string(),
+    };
+    let text = snapshot.to_json().to_string();
+    assert!(text.contains("\"9007199254740993\""), "{text}");
+    let restored = Snapshot::from_json(&json::parse(&text).unwrap()).unwrap();
+    assert_eq!(restored, snapshot);
+}
+
+#[test]
+fn older_versions_are_rejected() {
+    let text = r#"{"version": 1, "machine": "0", "state": "0", "head": 0, "steps": 1,
+                   "left": 0, "tape": "_"}"#;
+    assert!(matches!(
+        Snapshot::from_json(&json::parse(text).unwrap()),
+        Err(SnapshotError::Invalid(_))
+    ));
+}
+
+#[test]
+fn the_hash_ignores_comments_and_breakpoints() {
+    let plain = parse_machine(";I\n0 0 1 r 0\n0 _ _ l halt\n", ParseMode::Strict).unwrap();
+    let annotated = parse_machine(
+        ";I\n; Initial input: 00\n; header\n0 0 1 r 0 ! ; inline\n\n; leading\n0 _ _ l halt\n",
+        ParseMode::Strict,
+    )
+    .unwrap();
+    assert_eq!(machine_hash(&plain), machine_hash(&annotated));
+}
+
+#[test]
+fn the_hash_covers_rules_start_state_and_model() {
+    let machine = tm!(Infinite: 0 '0' => '1', R, 0; 0 '_' => '_', L, halt);
+    let hash = machine_hash(&machine);
+
+    let changed = tm!(Infinite: 0 '0' => '1', L, 0; 0 '_' => '_', L, halt);
+    assert_ne!(machine_hash(&changed), hash);
+    let reordered = tm!(Infinite: 0 '_' => '_', L, halt; 0 '0' => '1', R, 0);
+    assert_ne!(machine_hash(&reordered), hash);
+    let mut started = machine.clone();
+    started.start_state = "halt".to_string();
+    assert_ne!(machine_hash(&started), hash);
+    let sipser = tm!(Sipser: 0 '0' => '1', R, 0; 0 '_' => '_', L, halt);
+    assert_ne!(machine_hash(&sipser), hash);
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_saves_without_a_terminal() {
+    use std::process::{Command, Stdio};
+    use std::thread;
+    use std::time::Duration;
+
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("snapshot-ctrl-c");
+    fs::create_dir_all(&dir).unwrap();
+    let machine = dir.join("forever.in");
+    let saved = dir.join("forever.json");
+    let _ = fs::remove_file(&saved);
+    // Stays on one cell, so the run never ends and the tape never grows.
+    fs::write(&machine, ";I\n0 _ _ * 0\n").unwrap();
+
+    let child = Command::new(env!("CARGO_BIN_EXE_turing"))
+        .args(["simulate", "--max-steps", "1000000000000000", "--snapshot"])
+        .arg(&saved)
+        .arg(&machine)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(500));
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("interrupted, saved to"), "{stdout}");
+    let snapshot = Snapshot::load(&saved).unwrap();
+    assert!(snapshot.steps > 0);
+}
@@ -0,0 +1,62 @@
+//! The streamed converter against the in-memory one.
+
+use turing::model::Registry;
+use turing::stream::prepare;
+use turing::{ExportFormat, MachineType, ParseMode, convert_to, parse_machine};
+
+const EXAMPLE: &str = include_str!("../example.in");
+
+/// A machine using every annotation the text format has.
+const ANNOTATED: &str = "\
+;I
+; Initial input: 0110
+; Start state: 0
+; header
+
+; detached
+; leading
+0 0 1 r 1 ! ; inline
+1 * * l * ; wildcards
+
+0 1 * * halt_accept !
+* _ x r halt
+; trailing
+";
+
+/// Fails unless streaming `text` to `target` writes what converting it in memory renders,
+/// and reports the same sizes.
+fn assert_streams_like_memory(text: &str, target: Option<MachineType>) {
+    let machine = parse_machine(text, ParseMode::Strict).unwrap();
+    let (converted, rendered) = convert_to(&machine, target, ExportFormat::Text).unwrap();
+
+    let registry = Registry::standard();
+    let conversion = prepare(&registry, text.as_bytes(), None, target, ParseMode::Strict)
+        .unwrap()
+        .expect("text conversions stream");
+    let mut streamed = Vec::new();
+    let report = conversion.write(text.as_bytes(), &mut streamed).unwrap();
+
+    assert_eq!(String::from_utf8(streamed).unwrap(), rendered);
+    assert_eq!(report.target_model, converted.model);
+    assert_eq!(report.source_states, machine.states().len());
+    assert_eq!(report.source_transitions, machine.rules.len());
+    assert_eq!(report.output_states, converted.states().len());
+    assert_eq!(report.output_transitions, converted.rules.len());
+}
+
+#[test]
+fn example_streams_like_memory() {
+    assert_streams_like_memory(EXAMPLE, None);
+}
+
+#[test]
+fn annotations_stream_like_memory() {
+    assert_streams_like_memory(ANNOTATED, None);
+    assert_streams_like_memory(ANNOTATED, Some(MachineType::Sipser));
+}
+
+#[test]
+fn sipser_machines_stream_like_memory() {
+    let sipser = ANNOTATED.replacen(";I", ";S", 1);
+    assert_streams_like_memory(&sipser, Some(MachineType::Infinite));
+}
@@ -0,0 +1,37 @@
+//! Transition tables in Markdown and HTML.
+
+use turing::table::{to_html, to_markdown};
+use turing::tm;
+
+#[test]
+fn markdown_cells_escape_pipes_and_backticks() {
+    let machine = tm!(Infinite:
+        (r"a|b") '|' => '`', R, ("c`d");
+        ("c`d") '_' => '_', L, halt;
+    );
+    let table = to_markdown(&machine);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(
+        lines[0], "| state | `` ` `` | `\\|` | `_` | `#` | `$` |",
+        "{table}"
+    );
+    assert!(
+        lines[3].starts_with("| `a\\|b` | — | `` `,r,c`d `` |"),
+        "{table}"
+    );
+    assert!(lines[4].starts_with("| `` c`d `` |"), "{table}");
+}
+
+#[test]
+fn halt_state_rows_are_undefined() {
+    let machine = tm!(Infinite:
+        0 '_' => '1', R, halt;
+        halt '1' => '0', L, 0;
+        * '*' => '*', R, *;
+    );
+    let table = to_markdown(&machine);
+    let halt_row = table.lines().find(|l| l.starts_with("| `halt` |")).unwrap();
+    assert_eq!(halt_row, "| `halt` | — | — | — | — | — |", "{table}");
+    let html = to_html(&machine);
+    assert!(!html.contains("<code>0,l,0</code>"), "{html}");
+}
@@ -0,0 +1,125 @@
+//! `RunLengthTape` against the plain tapes it must behave like.
+
+use turing::Direction;
+use turing::tape::{DequeTape, RunLengthTape, Tape, VecTape};
+
+/// Fails unless `a` and `b` read the same over `from..=to` and have the same contents.
+fn assert_same(a: &impl Tape, b: &impl Tape, from: i64, to: i64) {
+    for position in from..=to {
+        assert_eq!(a.read(position), b.read(position), "at {position}");
+    }
+    assert_eq!(a.contents(), b.contents());
+}
+
+/// Applies the same writes to both tapes, checking them after each one.
+fn write_both(a: &mut impl Tape, b: &mut impl Tape, writes: &[(i64, char)]) {
+    for &(position, symbol) in writes {
+        a.write(position, symbol);
+        b.write(position, symbol);
+        assert_same(a, b, -12, 12);
+    }
+}
+
+#[test]
+fn writes_at_run_boundaries_match_a_deque_tape() {
+    let mut run_length = RunLengthTape::new("aaabbbccc", false);
+    let mut deque = DequeTape::new("aaabbbccc");
+    write_both(
+        &mut run_length,
+        &mut deque,
+        &[
+            // First and last cell of a run, splitting it.
+            (3, 'x'),
+            (5, 'y'),
+            // Extending the run before and after.
+            (2, 'b'),
+            (6, 'b'),
+            // Joining three runs into one, then splitting it again.
+            (3, 'b'),
+            (5, 'b'),
+            (4, 'z'),
+            // The first and last written cells.
+            (0, 'c'),
+            (8, 'a'),
+            // Blanks inside and at the ends.
+            (4, '_'),
+            (8, '_'),
+            (0, '_'),
+        ],
+    );
+    assert_eq!(run_length.runs(), 7);
+}
+
+#[test]
+fn growth_to_the_left_matches_a_deque_tape() {
+    let mut run_length = RunLengthTape::new("11", false);
+    let mut deque = DequeTape::new("11");
+    write_both(
+        &mut run_length,
+        &mut deque,
+        &[
+            (-1, '1'),
+            (-2, '1'),
+            (-5, '1'),
+            (-4, '_'),
+            (-3, '1'),
+            (-4, '1'),
+            (-8, '_'),
+            (-9, 'x'),
+        ],
+    );
+    assert_eq!(run_length.bounds(), deque.bounds());
+    assert_eq!(run_length.contents(), "x___1111111");
+    assert_eq!(run_length.step(-9, Direction::Left), -10);
+}
+
+#[test]
+fn growth_to_the_right_matches_a_vec_tape() {
+    let mut run_length = RunLengthTape::new("", true);
+    let mut vec = VecTape::new("");
+    write_both(
+        &mut run_length,
+        &mut vec,
+        &[(0, '1'), (4, '1'), (5, '1'), (2, '_'), (9, '_'), (3, '1')],
+    );
+    assert_eq!(run_length.bounds(), vec.bounds());
+}
+
+#[test]
+fn bounded_tapes_keep_the_head_on_the_first_cell() {
+    let run_length = RunLengthTape::new("01", true);
+    let vec = VecTape::new("01");
+    for position in 0..3 {
+        for direction in [Direction::Left, Direction::Right, Direction::Stay] {
+            assert_eq!(
+                run_length.step(position, direction),
+                vec.step(position, direction)
+            );
+        }
+    }
+    assert_eq!(run_length.step(0, Direction::Left), 0);
+    assert_eq!(run_length.left_edge(), vec.left_edge());
+    assert_eq!(run_length.run_floor(), Some(1));
+    assert_eq!(DequeTape::new("01").run_floor(), None);
+}
+
+#[test]
+fn pseudo_random_writes_match_a_deque_tape() {
+    let mut run_length = RunLengthTape::new("0110", false);
+    let mut deque = DequeTape::new("0110");
+    // A linear congruential generator, so the sequence is the same on every run.
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |range: u64| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % range
+    };
+    for _ in 0..2000 {
+        let position = next(21) as i64 - 10;
+        let symbol = ['0', '1', '_'][next(3) as usize];
+        run_length.write(position, symbol);
+        deque.write(position, symbol);
+    }
+    assert_same(&run_length, &deque, -12, 12);
+}